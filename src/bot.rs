@@ -1,21 +1,77 @@
 use crate::{
-    core::{adapter::NapcatAdapter, event::EventManager, plugin::PluginManager},
+    config::{Config, OneBotVersion},
+    core::{
+        adapter::NapcatAdapter,
+        event::EventManager,
+        limiter::RateLimiter,
+        middleware::ActionMiddleware,
+        onebot_v12::OneBotV12Adapter,
+        plugin::PluginManager,
+        record::{Recorder, RecordingAdapter, ReplayAdapter},
+    },
     prelude::ActionManager,
+    types::{adapter_type::Adapter, plugin_type::PluginWrapper},
 };
 use std::sync::Arc;
 pub struct MerilBot {
-    pub adapter: Arc<NapcatAdapter>,
+    pub adapter: Arc<dyn Adapter>,
     pub event: Arc<EventManager>,
     pub action: Arc<ActionManager>,
     pub plugin: Arc<PluginManager>,
 }
 
 impl MerilBot {
+    /// 按 Config 创建; 回放文件无法读取时 panic, 需要自行处理时用 try_new
     pub fn new() -> Self {
-        tracing_subscriber::fmt::init();
-        let adapter = NapcatAdapter::new();
-        let event = EventManager::new(adapter.clone().get_event_port());
-        let action = ActionManager::new(adapter.clone().get_action_port());
+        Self::try_new().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// 按 Config 创建, 回放文件无法读取时返回错误
+    pub fn try_new() -> std::io::Result<Self> {
+        let _ = tracing_subscriber::fmt::try_init();
+        let config = Config::get_or_init();
+        let (raw, recorder) = Self::raw_adapter(config)?;
+        let bot = match config.onebot_version() {
+            OneBotVersion::V11 => Self::with_adapter(raw),
+            OneBotVersion::V12 => Self::with_adapter(OneBotV12Adapter::new(raw)),
+        };
+        if let Some(recorder) = recorder {
+            bot.action.set_recorder(recorder);
+        }
+        Ok(bot)
+    }
+
+    /// 与协议端交换原始 JSON 的适配器: 回放文件, 或 (可选录制的) Napcat 连接;
+    /// 录制时一并返回写入端, 用于录制经由 HTTP 发送的动作
+    fn raw_adapter(config: &Config) -> std::io::Result<(Arc<dyn Adapter>, Option<Recorder>)> {
+        if !config.replay_file().is_empty() {
+            let replay = ReplayAdapter::from_file(config.replay_file()).map_err(|e| {
+                let message = format!("[Replay] 无法读取 {}: {}", config.replay_file(), e);
+                std::io::Error::new(e.kind(), message)
+            })?;
+            return Ok((Arc::new(replay), None));
+        }
+        let napcat = NapcatAdapter::new();
+        if config.record_dir().is_empty() {
+            return Ok((napcat, None));
+        }
+        match RecordingAdapter::in_dir(napcat.clone(), config.record_dir()) {
+            Ok(recording) => {
+                let recorder = recording.recorder();
+                Ok((recording, Some(recorder)))
+            }
+            Err(e) => {
+                tracing::error!("[Record] 无法创建录制文件: {}", e);
+                Ok((napcat, None))
+            }
+        }
+    }
+
+    /// 使用自定义适配器 (其他协议端、OneBot v12、测试用 Mock 等)
+    pub fn with_adapter(adapter: Arc<dyn Adapter>) -> Self {
+        let _ = tracing_subscriber::fmt::try_init();
+        let event = EventManager::new(adapter.get_event_port(), adapter.get_state_port());
+        let action = ActionManager::new(adapter.get_action_port(), adapter.get_state_port());
         let plugin = PluginManager::new(action.clone(), event.get_event_nexus());
         Self {
             event,
@@ -25,10 +81,31 @@ impl MerilBot {
         }
     }
 
+    /// 注册动作中间件, 在启动前调用
+    pub fn with_middleware(self, middleware: impl ActionMiddleware + 'static) -> Self {
+        self.action.add_middleware(middleware);
+        self
+    }
+
+    /// 注册插件, 在 run 之前调用; 内置的帮助与 AI 聊天插件总会加载, 其余插件 (如 RecallPlugin) 需自行注册
+    pub async fn register_plugin(&self, plugin: PluginWrapper) {
+        self.plugin.clone().add_plugin(plugin).await;
+    }
+
+    /// 替换所有插件共用的限流器, 在启动前调用; 默认取自 Config (MERIL_RATE_LIMIT_*)
+    pub fn with_rate_limiter(self, limiter: RateLimiter) -> Self {
+        self.action.set_rate_limiter(limiter);
+        self
+    }
+
     pub async fn run(&self) {
         self.adapter.clone().run();
         self.event.clone().run();
         self.action.clone().run();
+        self.action
+            .cache()
+            .clone()
+            .run(self.event.get_event_nexus());
         self.plugin.clone().run().await;
         let () = std::future::pending().await;
     }
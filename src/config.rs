@@ -3,22 +3,177 @@ use std::{collections::HashMap, sync::OnceLock};
 use tokio::sync::Mutex;
 pub static GLOBALSTATE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
 use serde::{Deserialize, Serialize};
+
+/// Napcat WebSocket 连接方向
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebSocketMode {
+    /// 反向 WS: 本地监听 websocket_addr, 等待 Napcat 连入
+    Reverse,
+    /// 正向 WS: 主动连接 Napcat 的 WebSocket 服务端
+    Forward,
+}
+
+impl WebSocketMode {
+    fn from_env() -> Self {
+        match std::env::var("NAPCAT_WEBSOCKET_MODE").as_deref() {
+            Ok("forward") => Self::Forward,
+            _ => Self::Reverse,
+        }
+    }
+}
+
+/// ActionManager 发送动作所用的通道
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionTransport {
+    /// 经由 WebSocket 发送, 通过 echo 匹配响应
+    WebSocket,
+    /// 以 HTTP POST 调用 Napcat 的 HTTP 服务端 (http_addr)
+    Http,
+}
+
+impl ActionTransport {
+    fn from_env() -> Self {
+        match std::env::var("NAPCAT_ACTION_TRANSPORT").as_deref() {
+            Ok("http") => Self::Http,
+            _ => Self::WebSocket,
+        }
+    }
+}
+
+/// 协议端使用的 OneBot 版本
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OneBotVersion {
+    #[serde(rename = "11")]
+    V11,
+    #[serde(rename = "12")]
+    V12,
+}
+
+impl OneBotVersion {
+    fn from_env() -> Self {
+        match std::env::var("ONEBOT_VERSION").as_deref() {
+            Ok("12") => Self::V12,
+            _ => Self::V11,
+        }
+    }
+}
+
+/// 令牌桶参数: 每秒补充 rate 条, 最多连续发送 burst 条; rate 为 0 表示不限速
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub rate: f64,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub const UNLIMITED: Self = Self {
+        rate: 0.0,
+        burst: 0,
+    };
+
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self { rate, burst }
+    }
+
+    /// 读取形如 "rate/burst" 的环境变量 (如 "0.5/3"), 未设置或格式错误时不限速
+    fn from_env(key: &str) -> Self {
+        std::env::var(key)
+            .ok()
+            .and_then(|value| {
+                let (rate, burst) = value.split_once('/')?;
+                Some(Self::new(
+                    rate.trim().parse().ok()?,
+                    burst.trim().parse().ok()?,
+                ))
+            })
+            .unwrap_or(Self::UNLIMITED)
+    }
+}
+
 #[derive(Getters, CloneGetters, Setters, Serialize, Deserialize, Clone)]
 pub struct Config {
     #[getset(get = "pub", set = "pub")]
     bot_id: i64,
+    /// 管理员 QQ 号 (MERIL_ROOT_ID), 0 表示未设置
     #[getset(get = "pub", set = "pub")]
     root_id: i64,
     #[getset(get = "pub", set = "pub")]
+    onebot_version: OneBotVersion,
+    #[getset(get = "pub", set = "pub")]
+    websocket_mode: WebSocketMode,
+    /// 本地监听地址: 反向 WS 的 `/ws` 与 HTTP 上报的 `/webhook`, 正向 WS 时只提供后者
+    #[getset(get = "pub", set = "pub")]
     websocket_addr: String,
+    /// 正向 WS 要连接的 Napcat 地址, 每个地址对应一个 QQ 账号
+    #[getset(get = "pub", set = "pub")]
+    napcat_websocket_urls: Vec<String>,
+    /// 正向 WS 断线重连的初始间隔 (毫秒), 每次失败后翻倍
+    #[getset(get = "pub", set = "pub")]
+    reconnect_interval: u64,
+    /// 正向 WS 断线重连的最大间隔 (毫秒)
+    #[getset(get = "pub", set = "pub")]
+    reconnect_max_interval: u64,
+    #[getset(get = "pub", set = "pub")]
+    action_transport: ActionTransport,
     #[getset(get = "pub", set = "pub")]
     http_addr: String,
+    /// 多账号时按账号指定的 Napcat HTTP 地址; 非空时未列出的账号不能经由 HTTP 发送
+    #[getset(get = "pub", set = "pub")]
+    http_accounts: HashMap<i64, String>,
     #[getset(get = "pub", set = "pub")]
     napcat_webui_token: String,
+    /// 反向 WS 校验、正向 WS 携带的 access token (NAPCAT_WEBSOCKET_TOKEN), 为空时不鉴权
     #[getset(get = "pub", set = "pub")]
     napcat_websocket_token: String,
     #[getset(get = "pub", set = "pub")]
     napcat_http_token: String,
+    /// HTTP 上报的签名密钥, 非空时校验 X-Signature
+    #[getset(get = "pub", set = "pub")]
+    napcat_http_secret: String,
+    /// HTTP 上报等待快速操作的最长时间 (毫秒)
+    #[getset(get = "pub", set = "pub")]
+    webhook_quick_timeout: u64,
+    /// Napcat 与本程序不在同一台机器时开启: 本地文件以 base64 内联发送, 而不是 file:// 路径
+    #[getset(get = "pub", set = "pub")]
+    inline_local_media: bool,
+    /// 演练模式: 写操作只记录日志而不发出, 查询照常进行
+    #[getset(get = "pub", set = "pub")]
+    dry_run: bool,
+    /// 动作等待响应的默认超时 (毫秒)
+    #[getset(get = "pub", set = "pub")]
+    action_timeout: u64,
+    /// 按动作名覆盖的超时 (毫秒)
+    #[getset(get = "pub", set = "pub")]
+    action_timeouts: HashMap<String, u64>,
+    /// 每个账号发送消息的总速率 (MERIL_RATE_LIMIT_GLOBAL, 形如 "1/5"), 默认不限速
+    #[getset(get = "pub", set = "pub")]
+    rate_limit_global: RateLimit,
+    /// 对同一好友发送消息的速率 (MERIL_RATE_LIMIT_USER), 默认不限速
+    #[getset(get = "pub", set = "pub")]
+    rate_limit_user: RateLimit,
+    /// 对同一群发送消息的速率 (MERIL_RATE_LIMIT_GROUP), 默认不限速
+    #[getset(get = "pub", set = "pub")]
+    rate_limit_group: RateLimit,
+    /// 模拟打字: 每个字符等待的时间 (毫秒, MERIL_TYPING_DELAY_PER_CHAR), 0 表示关闭
+    #[getset(get = "pub", set = "pub")]
+    typing_delay_per_char: u64,
+    /// 模拟打字的最长等待时间 (毫秒, MERIL_TYPING_DELAY_MAX)
+    #[getset(get = "pub", set = "pub")]
+    typing_delay_max: u64,
+    /// 好友、群与群成员信息缓存的有效期 (秒)
+    #[getset(get = "pub", set = "pub")]
+    cache_ttl: u64,
+    /// 记录最近发出的消息条数, 供撤回等操作使用
+    #[getset(get = "pub", set = "pub")]
+    sent_history: usize,
+    /// 原始流量录制目录, 非空时将事件与动作写入带时间戳的 JSONL 文件
+    #[getset(get = "pub", set = "pub")]
+    record_dir: String,
+    /// 回放的录制文件, 非空时不连接 Napcat, 改为回放该文件
+    #[getset(get = "pub", set = "pub")]
+    replay_file: String,
     #[getset(get = "pub", set = "pub")]
     ai_gemini_token: String,
     #[getset(get = "pub", set = "pub")]
@@ -30,12 +185,63 @@ impl Config {
     fn new() -> Self {
         Self {
             bot_id: 0,
-            root_id: 0,
+            root_id: std::env::var("MERIL_ROOT_ID")
+                .ok()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or(0),
+            onebot_version: OneBotVersion::from_env(),
+            websocket_mode: WebSocketMode::from_env(),
             websocket_addr: "0.0.0.0:3000".into(),
+            napcat_websocket_urls: std::env::var("NAPCAT_WEBSOCKET_URL")
+                .unwrap_or("ws://127.0.0.1:3001".to_string())
+                .split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect(),
+            reconnect_interval: 1000,
+            reconnect_max_interval: 60000,
+            action_transport: ActionTransport::from_env(),
             http_addr: "0.0.0.0:3001".into(),
+            // 形如 "10001=127.0.0.1:3001,10002=127.0.0.1:3002"
+            http_accounts: std::env::var("NAPCAT_HTTP_ACCOUNTS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .filter_map(|(id, addr)| Some((id.trim().parse().ok()?, addr.trim().to_string())))
+                .collect(),
             napcat_webui_token: "".into(),
-            napcat_websocket_token: "".into(),
+            napcat_websocket_token: std::env::var("NAPCAT_WEBSOCKET_TOKEN")
+                .unwrap_or("".to_string()),
             napcat_http_token: "".into(),
+            napcat_http_secret: std::env::var("NAPCAT_HTTP_SECRET").unwrap_or("".to_string()),
+            webhook_quick_timeout: 1000,
+            inline_local_media: std::env::var("MERIL_INLINE_LOCAL_MEDIA").is_ok_and(|v| v == "1"),
+            dry_run: std::env::var("MERIL_DRY_RUN").is_ok_and(|v| v == "1"),
+            action_timeout: std::env::var("MERIL_ACTION_TIMEOUT")
+                .ok()
+                .and_then(|timeout| timeout.parse().ok())
+                .unwrap_or(10000),
+            action_timeouts: HashMap::from([
+                ("get_login_info".to_string(), 3000),
+                ("get_status".to_string(), 3000),
+                ("upload_group_file".to_string(), 300000),
+                ("upload_private_file".to_string(), 300000),
+            ]),
+            rate_limit_global: RateLimit::from_env("MERIL_RATE_LIMIT_GLOBAL"),
+            rate_limit_user: RateLimit::from_env("MERIL_RATE_LIMIT_USER"),
+            rate_limit_group: RateLimit::from_env("MERIL_RATE_LIMIT_GROUP"),
+            typing_delay_per_char: std::env::var("MERIL_TYPING_DELAY_PER_CHAR")
+                .ok()
+                .and_then(|delay| delay.parse().ok())
+                .unwrap_or(0),
+            typing_delay_max: std::env::var("MERIL_TYPING_DELAY_MAX")
+                .ok()
+                .and_then(|delay| delay.parse().ok())
+                .unwrap_or(3000),
+            cache_ttl: 600,
+            sent_history: 200,
+            record_dir: std::env::var("MERIL_RECORD_DIR").unwrap_or("".to_string()),
+            replay_file: std::env::var("MERIL_REPLAY_FILE").unwrap_or("".to_string()),
             ai_gemini_token: std::env::var("GEMINI_API_KEY").unwrap_or("".to_string()),
             ai_deepseek_token: std::env::var("DEEPSEEK_API_KEY").unwrap_or("".to_string()),
         }
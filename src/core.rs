@@ -1,4 +1,11 @@
 pub mod action;
 pub mod adapter;
+pub mod cache;
+pub mod dispatchar;
 pub mod event;
+pub mod limiter;
+pub mod middleware;
+pub mod onebot_v12;
 pub mod plugin;
+pub mod record;
+pub mod sent;
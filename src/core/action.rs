@@ -1,96 +1,700 @@
 use crate::{
-    types::action_type::NapcatRequestData, types::message_type::Message,
+    config::{ActionTransport, Config},
+    core::{
+        adapter::QUICK_OPERATION_ACTION,
+        cache::InfoCache,
+        limiter::{Conversation, RateLimiter, SendPermit},
+        middleware::{ActionMiddleware, MiddlewareChain},
+        record::{RecordKind, Recorder},
+        sent::{SentMessage, SentStore},
+    },
+    types::action_type::{
+        ActionError, NapcatRequestData, QuickOperation, RetryPolicy,
+        response::{
+            CanSend, FileInfo, FileUrl, FriendInfo, GroupFiles, GroupInfo, GroupMemberInfo,
+            LoginInfo, MessageDetail, MessageHistory, MessageId, Status, UploadedFile, VersionInfo,
+        },
+    },
+    types::event_type::AnyEvent,
+    types::event_type::connection_event::{ConnectionEvent, ConnectionState},
+    types::message_type::{ForwardMessage, ForwardNode, MediaSource, Message},
     types::signal_type::SignalPort,
 };
 use dashmap::DashMap;
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde_json::{Value, json};
-use std::sync::Arc;
+use std::collections::HashMap;
 use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, RwLock};
 use tokio::sync::oneshot;
 use tokio::time;
 
+type PendingResult = Result<Value, ActionError>;
+
+/// 等待响应的请求及其发送账号 (None 表示未指定账号)
+struct PendingRequest {
+    self_id: Option<i64>,
+    tx: oneshot::Sender<PendingResult>,
+}
+
+/// 请求结束 (收到响应、超时或 future 被丢弃) 时从等待表中移除
+struct PendingGuard<'a> {
+    pending_requestions: &'a DashMap<String, PendingRequest>,
+    key: String,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending_requestions.remove(&self.key);
+    }
+}
+
+/// 以 OneBot v11 HTTP API 调用 Napcat: `POST {base_url}/{action}`.
+/// 每个 Napcat HTTP 服务端只对应一个账号; 多账号时用 with_account 为各账号指定地址,
+/// 此时指定了未登记账号的动作 (for_account) 会被拒绝, 未指定账号的动作发往 base_url
+#[derive(Clone)]
+pub struct HttpActionClient {
+    client: reqwest::Client,
+    base_url: String,
+    token: String,
+    accounts: HashMap<i64, String>,
+}
+
+impl HttpActionClient {
+    pub fn new(base_url: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: Self::normalize(base_url.into()),
+            token: token.into(),
+            accounts: HashMap::new(),
+        }
+    }
+
+    /// 为账号指定 Napcat HTTP 地址
+    pub fn with_account(mut self, self_id: i64, base_url: impl Into<String>) -> Self {
+        self.accounts
+            .insert(self_id, Self::normalize(base_url.into()));
+        self
+    }
+
+    fn normalize(base_url: String) -> String {
+        let base_url = if base_url.starts_with("http://") || base_url.starts_with("https://") {
+            base_url
+        } else {
+            format!("http://{}", base_url)
+        };
+        base_url.trim_end_matches('/').to_string()
+    }
+
+    fn base_url_for(&self, self_id: Option<i64>) -> Result<&str, ActionError> {
+        match self_id {
+            Some(self_id) if !self.accounts.is_empty() => self
+                .accounts
+                .get(&self_id)
+                .map(|url| url.as_str())
+                .ok_or(ActionError::Transport(format!(
+                    "no http endpoint for account {}",
+                    self_id
+                ))),
+            _ => Ok(&self.base_url),
+        }
+    }
+
+    async fn post(
+        &self,
+        self_id: Option<i64>,
+        action: &str,
+        params: &Value,
+        timeout: time::Duration,
+    ) -> Result<Value, ActionError> {
+        let base_url = self.base_url_for(self_id)?;
+        let mut request = self
+            .client
+            .post(format!("{}/{}", base_url, action))
+            .json(params);
+        if !self.token.is_empty() {
+            request = request.bearer_auth(&self.token);
+        }
+        let response = request.timeout(timeout).send().await.map_err(|e| {
+            tracing::warn!("[HttpError] {}", e);
+            if e.is_timeout() {
+                ActionError::Timeout
+            } else {
+                ActionError::Transport(e.to_string())
+            }
+        })?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED
+            || response.status() == reqwest::StatusCode::FORBIDDEN
+        {
+            return Err(ActionError::Transport("unauthorized".to_string()));
+        }
+        response
+            .json::<Value>()
+            .await
+            .map_err(|e| ActionError::Deserialize(e.to_string()))
+    }
+}
+
 pub struct ActionManager {
     ws_port: SignalPort<Value>,
-    pending_requestions: Arc<DashMap<String, oneshot::Sender<Value>>>,
-    pending_atomic: AtomicU64,
+    state_port: SignalPort<ConnectionEvent>,
+    http_client: Option<HttpActionClient>,
+    pending_requestions: Arc<DashMap<String, PendingRequest>>,
+    pending_atomic: Arc<AtomicU64>,
+    /// 发送消息的限流器, 所有副本共享 (with_rate_limiter 的副本除外)
+    limiter: Arc<RwLock<Arc<RateLimiter>>>,
+    cache: Arc<InfoCache>,
+    sent: Arc<SentStore>,
+    middleware: Arc<MiddlewareChain>,
+    /// 录制经由 HTTP 发送的动作与响应, 所有副本共享
+    recorder: Arc<RwLock<Option<Recorder>>>,
+    default_timeout: time::Duration,
+    /// 按动作名覆盖的超时, 所有副本共享
+    action_timeouts: Arc<DashMap<String, time::Duration>>,
+    self_id: Option<i64>,
+    timeout: Option<time::Duration>,
+    retry: Option<RetryPolicy>,
+    dry_run: bool,
+    /// 发送前将本地文件读取后以 base64 内联
+    inline_local_media: bool,
 }
 
 impl ActionManager {
-    pub fn new(ws_port: SignalPort<Value>) -> Arc<Self> {
+    pub fn new(ws_port: SignalPort<Value>, state_port: SignalPort<ConnectionEvent>) -> Arc<Self> {
+        let config = Config::get_or_init();
+        // 回放模式下动作必须由回放适配器应答
+        let http_client = match config.action_transport() {
+            _ if !config.replay_file().is_empty() => None,
+            ActionTransport::WebSocket => None,
+            ActionTransport::Http => Some(config.http_accounts().iter().fold(
+                HttpActionClient::new(config.http_addr(), config.napcat_http_token()),
+                |client, (self_id, addr)| client.with_account(*self_id, addr),
+            )),
+        };
+        Self::with_http_client(ws_port, state_port, http_client)
+    }
+
+    /// 指定 HTTP 通道; 为 None 时经由 WebSocket 发送
+    pub fn with_http_client(
+        ws_port: SignalPort<Value>,
+        state_port: SignalPort<ConnectionEvent>,
+        http_client: Option<HttpActionClient>,
+    ) -> Arc<Self> {
+        let config = Config::get_or_init();
+        let action_timeouts = config
+            .action_timeouts()
+            .iter()
+            .map(|(action, ms)| (action.clone(), time::Duration::from_millis(*ms)))
+            .collect();
         Arc::new(Self {
             ws_port,
+            state_port,
+            http_client,
             pending_requestions: Arc::new(DashMap::new()),
-            pending_atomic: AtomicU64::new(0),
+            pending_atomic: Arc::new(AtomicU64::new(0)),
+            limiter: Arc::new(RwLock::new(Arc::new(RateLimiter::from_config()))),
+            cache: Arc::new(InfoCache::from_config()),
+            sent: Arc::new(SentStore::from_config()),
+            middleware: Arc::new(MiddlewareChain::new()),
+            recorder: Arc::new(RwLock::new(None)),
+            default_timeout: time::Duration::from_millis(*config.action_timeout()),
+            action_timeouts: Arc::new(action_timeouts),
+            self_id: None,
+            timeout: None,
+            retry: None,
+            dry_run: *config.dry_run(),
+            inline_local_media: *config.inline_local_media(),
         })
     }
 
-    pub async fn request(&self, data: NapcatRequestData) -> Result<Value, &str> {
+    /// 与原实例共享连接与等待队列的副本
+    fn scoped(&self) -> Self {
+        Self {
+            ws_port: self.ws_port.clone(),
+            state_port: self.state_port.clone(),
+            http_client: self.http_client.clone(),
+            pending_requestions: self.pending_requestions.clone(),
+            pending_atomic: self.pending_atomic.clone(),
+            limiter: self.limiter.clone(),
+            cache: self.cache.clone(),
+            sent: self.sent.clone(),
+            middleware: self.middleware.clone(),
+            recorder: self.recorder.clone(),
+            default_timeout: self.default_timeout,
+            action_timeouts: self.action_timeouts.clone(),
+            self_id: self.self_id,
+            timeout: self.timeout,
+            retry: self.retry,
+            dry_run: self.dry_run,
+            inline_local_media: self.inline_local_media,
+        }
+    }
+
+    /// 返回以指定账号发送动作的 ActionManager, 与原实例共享连接与等待队列
+    pub fn for_account(&self, self_id: i64) -> Arc<Self> {
+        let mut scoped = self.scoped();
+        scoped.self_id = Some(self_id);
+        Arc::new(scoped)
+    }
+
+    /// 指定了账号时同 for_account, 否则返回沿用当前账号的副本
+    pub(crate) fn for_account_or_current(&self, self_id: Option<i64>) -> Arc<Self> {
+        let mut scoped = self.scoped();
+        scoped.self_id = self_id.or(self.self_id);
+        Arc::new(scoped)
+    }
+
+    /// 返回所有动作都使用指定超时的 ActionManager, 优先于按动作名的设置
+    pub fn with_timeout(&self, timeout: time::Duration) -> Arc<Self> {
+        let mut scoped = self.scoped();
+        scoped.timeout = Some(timeout);
+        Arc::new(scoped)
+    }
+
+    /// 返回按策略重试幂等动作的 ActionManager
+    pub fn with_retry(&self, policy: RetryPolicy) -> Arc<Self> {
+        let mut scoped = self.scoped();
+        scoped.retry = Some(policy);
+        Arc::new(scoped)
+    }
+
+    /// 返回使用指定限流器的 ActionManager, 其余设置与连接照旧共享;
+    /// 要替换所有实例共用的限流器见 set_rate_limiter
+    pub fn with_rate_limiter(&self, limiter: RateLimiter) -> Arc<Self> {
+        let mut scoped = self.scoped();
+        scoped.limiter = Arc::new(RwLock::new(Arc::new(limiter)));
+        Arc::new(scoped)
+    }
+
+    /// 返回发送前是否内联本地文件 (见 Config::inline_local_media) 的 ActionManager
+    pub fn with_inline_local_media(&self, inline: bool) -> Arc<Self> {
+        let mut scoped = self.scoped();
+        scoped.inline_local_media = inline;
+        Arc::new(scoped)
+    }
+
+    /// 返回演练模式的 ActionManager: 写操作只记录日志并返回伪造的成功响应, 查询照常发出
+    pub fn dry_run(&self) -> Arc<Self> {
+        let mut scoped = self.scoped();
+        scoped.dry_run = true;
+        Arc::new(scoped)
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// 设置某个动作的默认超时, 对所有共享连接的实例生效
+    pub fn set_action_timeout(&self, action: impl Into<String>, timeout: time::Duration) {
+        self.action_timeouts.insert(action.into(), timeout);
+    }
+
+    /// 好友、群与群成员信息缓存, 所有副本共享
+    pub fn cache(&self) -> &Arc<InfoCache> {
+        &self.cache
+    }
+
+    /// 最近发出的消息, 所有副本共享
+    pub fn sent_store(&self) -> &Arc<SentStore> {
+        &self.sent
+    }
+
+    /// 注册动作中间件, 对所有共享连接的实例生效
+    pub fn add_middleware(&self, middleware: impl ActionMiddleware + 'static) {
+        self.middleware.push(Arc::new(middleware));
+    }
+
+    /// 替换发送消息所用的限流器, 对所有共享连接的实例生效; 已在排队的消息仍按原限流器发出
+    pub fn set_rate_limiter(&self, limiter: RateLimiter) {
+        *self.limiter.write().unwrap() = Arc::new(limiter);
+    }
+
+    /// 将经由 HTTP 发送的动作与响应写入录制文件 (WebSocket 流量由 RecordingAdapter 录制),
+    /// 对所有共享连接的实例生效
+    pub fn set_recorder(&self, recorder: Recorder) {
+        *self.recorder.write().unwrap() = Some(recorder);
+    }
+
+    fn record(&self, kind: RecordKind, data: &Value) {
+        if let Some(recorder) = self.recorder.read().unwrap().as_ref() {
+            recorder.record(kind, data);
+        }
+    }
+
+    /// 正在等待响应的请求数
+    pub fn pending_count(&self) -> usize {
+        self.pending_requestions.len()
+    }
+
+    /// 某个动作实际使用的超时
+    pub fn timeout_for(&self, action: &str) -> time::Duration {
+        self.timeout
+            .or_else(|| self.action_timeouts.get(action).map(|timeout| *timeout))
+            .unwrap_or(self.default_timeout)
+    }
+
+    pub fn self_id(&self) -> Option<i64> {
+        self.self_id
+    }
+
+    /// 发送消息所用的限流器, 可读取各会话的队列深度
+    pub fn rate_limiter(&self) -> Arc<RateLimiter> {
+        self.limiter.read().unwrap().clone()
+    }
+
+    /// 发送动作并返回完整响应; 协议端返回 status: failed 时为 ActionError::Failed.
+    /// 丢弃返回的 future 即取消请求, 不会在等待表中留下记录
+    pub async fn request(&self, data: NapcatRequestData) -> Result<Value, ActionError> {
+        self.request_limited(data, None).await
+    }
+
+    /// 同 request; 指定会话时在通过中间件后排队限流, 被拦截的消息不占用令牌
+    async fn request_limited(
+        &self,
+        data: NapcatRequestData,
+        limit: Option<(Conversation, usize)>,
+    ) -> Result<Value, ActionError> {
+        // 先补上账号, 中间件可据此区分
+        let data = match (data.self_id(), self.self_id) {
+            (None, Some(self_id)) => data.with_self_id(self_id),
+            _ => data,
+        };
+        self.middleware
+            .run(data, |data| async move {
+                let permit = match limit {
+                    Some((target, text_len)) => {
+                        let limiter = self.rate_limiter();
+                        Some(limiter.acquire(data.self_id(), target, text_len).await)
+                    }
+                    None => None,
+                };
+                self.request_or_dry_run(data, permit).await
+            })
+            .await
+    }
+
+    async fn request_or_dry_run(
+        &self,
+        data: NapcatRequestData,
+        permit: Option<SendPermit>,
+    ) -> Result<Value, ActionError> {
+        if !self.dry_run || RetryPolicy::is_idempotent(data.action()) {
+            return self.request_with_retry(data, permit).await;
+        }
+        drop(permit);
+        tracing::info!("[DryRun] [{}] {}", data.action(), data.params());
+        // 伪造的消息 ID 为负数, 不会与真实消息冲突
         let key = self
             .pending_atomic
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-        let data = data.with_echo(key.to_string());
-        let value = if let Ok(value) = serde_json::to_value(&data) {
-            value
-        } else {
-            return Err("Serde Error");
+        let message_id = -(key as i64) - 1;
+        Ok(json!({
+            "status": "ok",
+            "retcode": 0,
+            "data": { "message_id": message_id },
+            "message": "",
+            "wording": "",
+            "echo": key.to_string(),
+        }))
+    }
+
+    async fn request_with_retry(
+        &self,
+        data: NapcatRequestData,
+        permit: Option<SendPermit>,
+    ) -> Result<Value, ActionError> {
+        let Some(policy) = self
+            .retry
+            .filter(|_| RetryPolicy::is_idempotent(data.action()))
+        else {
+            return self.request_once(data, permit).await;
         };
+        // 发送消息不会重试, 限流许可只用于第一次
+        let mut permit = permit;
+        let mut attempt = 0;
+        loop {
+            match self.request_once(data.clone(), permit.take()).await {
+                Err(e) if attempt < policy.max_retries && RetryPolicy::should_retry(&e) => {
+                    tracing::warn!(
+                        "[Retry] [{}] 第 {} 次重试: {}",
+                        data.action(),
+                        attempt + 1,
+                        e
+                    );
+                    time::sleep(policy.delay(attempt)).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// 发出一次请求; 限流许可在请求交给传输层后即释放, 不会在等待响应期间阻塞同一会话
+    async fn request_once(
+        &self,
+        data: NapcatRequestData,
+        permit: Option<SendPermit>,
+    ) -> Result<Value, ActionError> {
+        let key = self
+            .pending_atomic
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let data = data.with_echo(key.to_string());
+        let value =
+            serde_json::to_value(&data).map_err(|e| ActionError::Serialize(e.to_string()))?;
         tracing::info!(
             "<<[{}] {}",
             value["action"].as_str().unwrap_or(""),
             value["params"]
         );
+        let action = value["action"].as_str().unwrap_or("");
+        let timeout = self.timeout_for(action);
+        // 快速操作需经过适配器, 以便作为 HTTP 上报的响应返回
+        if let Some(http_client) = &self.http_client
+            && action != QUICK_OPERATION_ACTION
+        {
+            self.record(RecordKind::Action, &value);
+            // HTTP 请求各自独立, 需等到响应才能保证同一会话的顺序
+            let res = http_client
+                .post(data.self_id(), action, &value["params"], timeout)
+                .await;
+            drop(permit);
+            let mut res = res?;
+            res["echo"] = Value::String(key.to_string());
+            self.record(RecordKind::Response, &res);
+            return Self::check_status(action, res);
+        }
+        let (tx, rx) = oneshot::channel::<PendingResult>();
+        let pending = PendingRequest {
+            self_id: data.self_id(),
+            tx,
+        };
+        self.pending_requestions.insert(key.to_string(), pending);
+        let _guard = PendingGuard {
+            pending_requestions: &self.pending_requestions,
+            key: key.to_string(),
+        };
         let _ = self.ws_port.send(value.clone());
-        let (tx, rx) = oneshot::channel::<Value>();
-        self.pending_requestions.insert(key.to_string(), tx);
-        let Ok(Ok(res)) = time::timeout(time::Duration::from_secs(10), rx).await else {
-            tracing::warn!("[TimeOutError] ActionTimeOut");
-            return Err("Time Out Error");
+        // 适配器按顺序转发, 交出后即可让同一会话的下一条消息发出
+        drop(permit);
+        let Ok(Ok(res)) = time::timeout(timeout, rx).await else {
+            tracing::warn!("[TimeOutError] [{}] {:?} 内未收到响应", action, timeout);
+            return Err(ActionError::Timeout);
         };
-        Ok(res)
+        Self::check_status(action, res?)
+    }
+
+    fn check_status(action: &str, res: Value) -> Result<Value, ActionError> {
+        match ActionError::from_response(&res) {
+            Some(e) => {
+                tracing::warn!("[ActionFailed] [{}] {}", action, e);
+                Err(e)
+            }
+            None => Ok(res),
+        }
+    }
+
+    /// 发送动作并将响应的 data 反序列化为 T
+    pub async fn call<T: DeserializeOwned>(
+        &self,
+        action: &str,
+        params: Value,
+    ) -> Result<T, ActionError> {
+        let data = NapcatRequestData::new()
+            .with_action(action)
+            .with_params(params);
+        let mut res = self.request(data).await?;
+        Self::deserialize(action, res["data"].take())
+    }
+
+    fn deserialize<T: DeserializeOwned>(action: &str, data: Value) -> Result<T, ActionError> {
+        serde_json::from_value(data).map_err(|e| {
+            tracing::warn!("[SerdeError] [{}] {}", action, e);
+            ActionError::Deserialize(e.to_string())
+        })
+    }
+
+    /// 发送不关心返回数据的动作
+    async fn call_unit(&self, action: &str, params: Value) -> Result<(), ActionError> {
+        self.call::<IgnoredAny>(action, params).await.map(|_| ())
+    }
+
+    /// 连接断开时立即让该账号等待中的请求失败, 而不是等到超时; 未指定账号的请求一并失败
+    fn fail_pending(&self, self_id: i64, reason: &'static str) -> usize {
+        let keys: Vec<String> = self
+            .pending_requestions
+            .iter()
+            .filter(|entry| entry.self_id.is_none_or(|id| id == self_id))
+            .map(|entry| entry.key().clone())
+            .collect();
+        let mut count = 0;
+        for key in keys {
+            if let Some((_, pending)) = self.pending_requestions.remove(&key) {
+                let _ = pending
+                    .tx
+                    .send(Err(ActionError::TransportClosed(reason.to_string())));
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// 对触发事件执行快速操作 (回复、撤回、踢出、禁言、处理请求等)
+    pub async fn handle_quick_operation(
+        &self,
+        context: impl Into<AnyEvent>,
+        operation: QuickOperation,
+    ) -> Result<Value, ActionError> {
+        let value = json!({
+            "context": context.into(),
+            "operation": operation,
+        });
+        let data = NapcatRequestData::new()
+            .with_action(QUICK_OPERATION_ACTION)
+            .with_params(value);
+        self.request(data).await
     }
 
     pub async fn send_private_message(
         &self,
         user_id: i64,
         message: Message,
-    ) -> Result<Value, &str> {
+    ) -> Result<SentMessage, ActionError> {
+        self.send_message(Conversation::Private(user_id), message)
+            .await
+    }
+
+    pub async fn send_group_message(
+        &self,
+        group_id: i64,
+        message: Message,
+    ) -> Result<SentMessage, ActionError> {
+        self.send_message(Conversation::Group(group_id), message)
+            .await
+    }
+
+    /// 向任一会话发送消息
+    pub async fn send_message(
+        &self,
+        target: Conversation,
+        message: Message,
+    ) -> Result<SentMessage, ActionError> {
+        let text_len = message.text_len();
+        let message = if self.inline_local_media {
+            message.inline_local_media().await
+        } else {
+            message
+        };
+        let (act, value) = match target {
+            Conversation::Private(user_id) => (
+                "send_private_msg",
+                json!({
+                    "user_id": user_id,
+                    "message": message
+                }),
+            ),
+            Conversation::Group(group_id) => (
+                "send_group_msg",
+                json!({
+                    "group_id": group_id,
+                    "message": message
+                }),
+            ),
+        };
+        self.send_to(target, act, value, text_len).await
+    }
+
+    async fn inline_forward(&self, forward: ForwardMessage) -> ForwardMessage {
+        if self.inline_local_media {
+            forward.inline_local_media().await
+        } else {
+            forward
+        }
+    }
+
+    /// 发送群合并转发
+    pub async fn send_group_forward_msg(
+        &self,
+        group_id: i64,
+        forward: ForwardMessage,
+    ) -> Result<SentMessage, ActionError> {
+        let forward = self.inline_forward(forward).await;
+        let value = json!({
+            "group_id": group_id,
+            "messages": forward,
+        });
+        let target = Conversation::Group(group_id);
+        self.send_to(target, "send_group_forward_msg", value, 0)
+            .await
+    }
+
+    /// 发送私聊合并转发
+    pub async fn send_private_forward_msg(
+        &self,
+        user_id: i64,
+        forward: ForwardMessage,
+    ) -> Result<SentMessage, ActionError> {
+        let forward = self.inline_forward(forward).await;
         let value = json!({
             "user_id": user_id,
-            "message": message
+            "messages": forward,
         });
-        let act = "send_private_msg";
-        let data = NapcatRequestData::new().with_action(act).with_params(value);
-        self.request(data).await
+        let target = Conversation::Private(user_id);
+        self.send_to(target, "send_private_forward_msg", value, 0)
+            .await
+    }
+
+    /// 经限流发出消息, 并记录到最近发送的消息中
+    async fn send_to(
+        &self,
+        target: Conversation,
+        action: &str,
+        params: Value,
+        text_len: usize,
+    ) -> Result<SentMessage, ActionError> {
+        let data = NapcatRequestData::new()
+            .with_action(action)
+            .with_params(params);
+        let mut res = self.request_limited(data, Some((target, text_len))).await?;
+        let MessageId { message_id } = Self::deserialize(action, res["data"].take())?;
+        let sent = SentMessage {
+            message_id,
+            self_id: self.self_id,
+            target,
+            time: chrono::Utc::now().timestamp(),
+        };
+        // 演练模式的消息 ID 是伪造的, 不记录
+        if !self.dry_run {
+            self.sent.push(sent.clone());
+        }
+        Ok(sent)
     }
 
-    pub async fn send_group_message(&self, group_id: i64, message: Message) -> Result<Value, &str> {
+    /// 获取合并转发的内容, id 为 forward 消息段中的 id
+    pub async fn get_forward_msg(&self, id: &str) -> Result<ForwardMessage, ActionError> {
         let value = json!({
-            "group_id": group_id,
-            "message": message
+            "message_id": id,
+            "id": id,
         });
-        let act = "send_group_msg";
-        let data = NapcatRequestData::new().with_action(act).with_params(value);
-        self.request(data).await
+        // 各实现返回 { messages: [...] } 或直接返回数组
+        let data: Value = self.call("get_forward_msg", value).await?;
+        let messages = match data {
+            Value::Object(mut data) => data.remove("messages").unwrap_or(Value::Null),
+            data => data,
+        };
+        serde_json::from_value::<Vec<ForwardNode>>(messages)
+            .map(ForwardMessage::from)
+            .map_err(|e| ActionError::Deserialize(e.to_string()))
     }
 
-    pub async fn send_like(&self, user_id: i64, times: i32) -> Result<String, &str> {
+    pub async fn send_like(&self, user_id: i64, times: i32) -> Result<(), ActionError> {
         let value = json!({
             "user_id": user_id,
             "times": times
         });
-        let act = "send_like";
-        let data = NapcatRequestData::new().with_action(act).with_params(value);
-        let message = self.request(data).await.ok().and_then(|value| {
-            value
-                .get("message")
-                .and_then(|message| message.get("message"))
-                .and_then(|message| message.as_str())
-                .map(|message| message.to_string())
-        });
-        message.ok_or("Send Like Error.")
+        self.call_unit("send_like", value).await
     }
 
-    pub async fn send_private_poke(&self, user_id: i64) -> Result<Value, &str> {
+    pub async fn send_private_poke(&self, user_id: i64) -> Result<Value, ActionError> {
         let act = "friend_poke";
         let value = json!({
             "user_id": user_id,
@@ -99,7 +703,7 @@ impl ActionManager {
         self.request(data).await
     }
 
-    pub async fn send_group_poke(&self, group_id: i64, user_id: i64) -> Result<Value, &str> {
+    pub async fn send_group_poke(&self, group_id: i64, user_id: i64) -> Result<Value, ActionError> {
         let act = "group_poke";
         let value = json!({
             "user_id": user_id,
@@ -109,7 +713,371 @@ impl ActionManager {
         self.request(data).await
     }
 
+    /// 撤回消息
+    pub async fn delete_msg(&self, message_id: i64) -> Result<(), ActionError> {
+        self.call_unit("delete_msg", json!({ "message_id": message_id }))
+            .await?;
+        self.sent.remove(message_id);
+        Ok(())
+    }
+
+    pub async fn get_msg(&self, message_id: i64) -> Result<MessageDetail, ActionError> {
+        self.call("get_msg", json!({ "message_id": message_id }))
+            .await
+    }
+
+    pub async fn get_login_info(&self) -> Result<LoginInfo, ActionError> {
+        self.call("get_login_info", json!({})).await
+    }
+
+    pub async fn get_friend_list(&self) -> Result<Vec<FriendInfo>, ActionError> {
+        self.call("get_friend_list", json!({})).await
+    }
+
+    pub async fn get_group_list(&self) -> Result<Vec<GroupInfo>, ActionError> {
+        self.call("get_group_list", json!({})).await
+    }
+
+    pub async fn get_group_info(
+        &self,
+        group_id: i64,
+        no_cache: bool,
+    ) -> Result<GroupInfo, ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "no_cache": no_cache,
+        });
+        self.call("get_group_info", value).await
+    }
+
+    pub async fn get_group_member_info(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        no_cache: bool,
+    ) -> Result<GroupMemberInfo, ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "user_id": user_id,
+            "no_cache": no_cache,
+        });
+        self.call("get_group_member_info", value).await
+    }
+
+    pub async fn get_group_member_list(
+        &self,
+        group_id: i64,
+    ) -> Result<Vec<GroupMemberInfo>, ActionError> {
+        self.call("get_group_member_list", json!({ "group_id": group_id }))
+            .await
+    }
+
+    /// 踢出群成员, reject_add_request 为 true 时拒绝其再次加群
+    pub async fn set_group_kick(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        reject_add_request: bool,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "user_id": user_id,
+            "reject_add_request": reject_add_request,
+        });
+        self.call_unit("set_group_kick", value).await
+    }
+
+    /// 禁言群成员 (秒), 0 表示解除禁言
+    pub async fn set_group_ban(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        duration: i64,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "user_id": user_id,
+            "duration": duration,
+        });
+        self.call_unit("set_group_ban", value).await
+    }
+
+    pub async fn set_group_whole_ban(
+        &self,
+        group_id: i64,
+        enable: bool,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "enable": enable,
+        });
+        self.call_unit("set_group_whole_ban", value).await
+    }
+
+    /// 禁言匿名用户, flag 为匿名消息中的 anonymous.flag
+    pub async fn set_group_anonymous_ban(
+        &self,
+        group_id: i64,
+        flag: &str,
+        duration: i64,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "anonymous_flag": flag,
+            "flag": flag,
+            "duration": duration,
+        });
+        self.call_unit("set_group_anonymous_ban", value).await
+    }
+
+    pub async fn set_group_admin(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        enable: bool,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "user_id": user_id,
+            "enable": enable,
+        });
+        self.call_unit("set_group_admin", value).await
+    }
+
+    /// 设置群名片, 为空时取消名片
+    pub async fn set_group_card(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        card: &str,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "user_id": user_id,
+            "card": card,
+        });
+        self.call_unit("set_group_card", value).await
+    }
+
+    pub async fn set_group_name(&self, group_id: i64, group_name: &str) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "group_name": group_name,
+        });
+        self.call_unit("set_group_name", value).await
+    }
+
+    /// 退出群聊, is_dismiss 为 true 且为群主时解散群
+    pub async fn set_group_leave(
+        &self,
+        group_id: i64,
+        is_dismiss: bool,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "is_dismiss": is_dismiss,
+        });
+        self.call_unit("set_group_leave", value).await
+    }
+
+    /// 处理加好友请求, flag 来自请求事件
+    pub async fn set_friend_add_request(
+        &self,
+        flag: &str,
+        approve: bool,
+        remark: &str,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "flag": flag,
+            "approve": approve,
+            "remark": remark,
+        });
+        self.call_unit("set_friend_add_request", value).await
+    }
+
+    /// 处理加群请求或邀请, sub_type 为 add / invite, reason 为拒绝理由
+    pub async fn set_group_add_request(
+        &self,
+        flag: &str,
+        sub_type: &str,
+        approve: bool,
+        reason: &str,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "flag": flag,
+            "sub_type": sub_type,
+            "approve": approve,
+            "reason": reason,
+        });
+        self.call_unit("set_group_add_request", value).await
+    }
+
+    /// 获取图片文件, file 为图片消息段中的 file
+    pub async fn get_image(&self, file: &str) -> Result<FileInfo, ActionError> {
+        self.call("get_image", json!({ "file": file })).await
+    }
+
+    /// 获取语音文件并转换为 out_format (如 mp3)
+    pub async fn get_record(&self, file: &str, out_format: &str) -> Result<FileInfo, ActionError> {
+        let value = json!({
+            "file": file,
+            "out_format": out_format,
+        });
+        self.call("get_record", value).await
+    }
+
+    pub async fn can_send_image(&self) -> Result<bool, ActionError> {
+        self.call::<CanSend>("can_send_image", json!({}))
+            .await
+            .map(|res| res.yes)
+    }
+
+    pub async fn get_status(&self) -> Result<Status, ActionError> {
+        self.call("get_status", json!({})).await
+    }
+
+    pub async fn get_version_info(&self) -> Result<VersionInfo, ActionError> {
+        self.call("get_version_info", json!({})).await
+    }
+
+    fn upload_name(file: &MediaSource, name: &str) -> String {
+        if !name.is_empty() {
+            return name.to_string();
+        }
+        file.file_name().unwrap_or("file".to_string())
+    }
+
+    /// 上传群文件, name 为空时取源文件名, folder 为目标文件夹 ID (None 为根目录);
+    /// 返回文件 ID (若协议端提供)
+    pub async fn upload_group_file(
+        &self,
+        group_id: i64,
+        file: impl Into<MediaSource>,
+        name: &str,
+        folder: Option<&str>,
+    ) -> Result<Option<String>, ActionError> {
+        let file = file.into();
+        let mut value = json!({
+            "group_id": group_id,
+            "file": file.encode_with(self.inline_local_media).await,
+            "name": Self::upload_name(&file, name),
+        });
+        if let Some(folder) = folder {
+            value["folder"] = json!(folder);
+            value["folder_id"] = json!(folder);
+        }
+        self.call::<Option<UploadedFile>>("upload_group_file", value)
+            .await
+            .map(|res| res.and_then(|res| res.file_id))
+    }
+
+    /// 向好友发送文件, name 为空时取源文件名; 返回文件 ID (若协议端提供)
+    pub async fn upload_private_file(
+        &self,
+        user_id: i64,
+        file: impl Into<MediaSource>,
+        name: &str,
+    ) -> Result<Option<String>, ActionError> {
+        let file = file.into();
+        let value = json!({
+            "user_id": user_id,
+            "file": file.encode_with(self.inline_local_media).await,
+            "name": Self::upload_name(&file, name),
+        });
+        self.call::<Option<UploadedFile>>("upload_private_file", value)
+            .await
+            .map(|res| res.and_then(|res| res.file_id))
+    }
+
+    /// 获取群文件下载链接
+    pub async fn get_group_file_url(
+        &self,
+        group_id: i64,
+        file_id: &str,
+        busid: i64,
+    ) -> Result<String, ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "file_id": file_id,
+            "busid": busid,
+        });
+        self.call::<FileUrl>("get_group_file_url", value)
+            .await
+            .map(|res| res.url)
+    }
+
+    /// 获取群根目录的文件与文件夹
+    pub async fn get_group_root_files(&self, group_id: i64) -> Result<GroupFiles, ActionError> {
+        self.call("get_group_root_files", json!({ "group_id": group_id }))
+            .await
+    }
+
+    /// 创建群文件夹, parent_id 为 None 时在根目录创建
+    pub async fn create_group_file_folder(
+        &self,
+        group_id: i64,
+        name: &str,
+        parent_id: Option<&str>,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "group_id": group_id,
+            "name": name,
+            "folder_name": name,
+            "parent_id": parent_id.unwrap_or("/"),
+        });
+        self.call_unit("create_group_file_folder", value).await
+    }
+
+    /// 对消息贴表情 (Napcat 扩展), emoji_id 为 QQ 表情 ID
+    pub async fn set_msg_emoji_like(
+        &self,
+        message_id: i64,
+        emoji_id: &str,
+        set: bool,
+    ) -> Result<(), ActionError> {
+        let value = json!({
+            "message_id": message_id,
+            "emoji_id": emoji_id,
+            "set": set,
+        });
+        self.call_unit("set_msg_emoji_like", value).await
+    }
+
+    /// 标记消息已读 (Napcat 扩展)
+    pub async fn mark_msg_as_read(&self, message_id: i64) -> Result<(), ActionError> {
+        self.call_unit("mark_msg_as_read", json!({ "message_id": message_id }))
+            .await
+    }
+
+    /// 获取群消息历史 (Napcat 扩展), message_seq 为 None 时从最新消息开始
+    pub async fn get_group_msg_history(
+        &self,
+        group_id: i64,
+        message_seq: Option<i64>,
+        count: i32,
+        reverse_order: bool,
+    ) -> Result<Vec<MessageDetail>, ActionError> {
+        let mut value = json!({
+            "group_id": group_id,
+            "count": count,
+            "reverseOrder": reverse_order,
+        });
+        if let Some(message_seq) = message_seq {
+            value["message_seq"] = json!(message_seq);
+        }
+        self.call::<MessageHistory>("get_group_msg_history", value)
+            .await
+            .map(|res| res.messages)
+    }
+
+    /// 设置群精华消息 (Napcat 扩展)
+    pub async fn set_essence_msg(&self, message_id: i64) -> Result<(), ActionError> {
+        self.call_unit("set_essence_msg", json!({ "message_id": message_id }))
+            .await
+    }
+
     pub fn run(self: Arc<Self>) {
+        let arc_self = self.clone();
         let fut = async move {
             loop {
                 let Ok(res) = self.ws_port.recv().await else {
@@ -123,11 +1091,29 @@ impl ActionManager {
                     continue;
                 };
 
-                echo.map(|echo| {
-                    if let Some((_, tx)) = self.pending_requestions.remove(&echo.to_string()) {
-                        let _ = tx.send(res.clone());
-                    };
-                });
+                if let Some(echo) = echo
+                    && let Some((_, pending)) = self.pending_requestions.remove(&echo.to_string())
+                {
+                    let _ = pending.tx.send(Ok(res.clone()));
+                }
+            }
+        };
+        tokio::spawn(fut);
+        let fut = async move {
+            loop {
+                let Ok(state) = arc_self.state_port.recv().await else {
+                    continue;
+                };
+                if state.state == ConnectionState::Disconnected {
+                    let count = arc_self.fail_pending(state.self_id, "Connection Lost");
+                    if count > 0 {
+                        tracing::warn!(
+                            "[Connection Lost] [id = {}] 取消 {} 个等待中的请求",
+                            state.self_id,
+                            count
+                        );
+                    }
+                }
             }
         };
         tokio::spawn(fut);
@@ -1,99 +1,621 @@
-use crate::config::Config;
+use crate::config::{Config, WebSocketMode};
+use crate::types::adapter_type::Adapter;
+use crate::types::event_type::connection_event::ConnectionEvent;
 use crate::types::signal_type::{SignalHub, SignalPort};
 use axum::{
-    Router,
+    Json, Router,
+    body::Bytes,
     extract::{
-        State,
+        ConnectInfo, Query, State,
         ws::{Message, WebSocket, WebSocketUpgrade},
     },
-    routing::any,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    response::{IntoResponse, Response},
+    routing::{any, post},
 };
-use serde_json::Value;
-use std::sync::Arc;
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha1::Sha1;
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest, http::HeaderValue};
+
+pub const QUICK_OPERATION_ACTION: &str = ".handle_quick_operation";
+
+/// NapcatAdapter 的设置, 默认取自 Config
+#[derive(Clone, Debug)]
+pub struct NapcatOptions {
+    /// 未指定账号的动作交给此账号; 为 None 时仅在只有一条连接时发送, 否则拒绝
+    pub default_account: Option<i64>,
+    /// 反向 WS 校验、正向 WS 握手携带的 token, 为空时不鉴权
+    pub access_token: String,
+    /// HTTP 上报的签名密钥, 非空时校验 X-Signature
+    pub http_secret: String,
+    /// HTTP 上报等待快速操作的最长时间
+    pub quick_timeout: Duration,
+}
+
+impl Default for NapcatOptions {
+    fn default() -> Self {
+        Self {
+            default_account: None,
+            access_token: String::new(),
+            http_secret: String::new(),
+            quick_timeout: Duration::from_millis(1000),
+        }
+    }
+}
+
+impl NapcatOptions {
+    pub fn from_config() -> Self {
+        let config = Config::get_or_init();
+        Self {
+            default_account: Some(*config.bot_id()).filter(|id| *id != 0),
+            access_token: config.napcat_websocket_token().clone(),
+            http_secret: config.napcat_http_secret().clone(),
+            quick_timeout: Duration::from_millis(*config.webhook_quick_timeout()),
+        }
+    }
+}
 
 pub struct NapcatAdapter {
     ws_event_hub: Arc<SignalHub<Value>>,
     ws_action_hub: Arc<SignalHub<Value>>,
+    state_hub: Arc<SignalHub<ConnectionEvent>>,
+    quick_slots: DashMap<String, oneshot::Sender<Value>>,
+    connections: DashMap<i64, mpsc::UnboundedSender<Value>>,
+    options: NapcatOptions,
+}
+
+impl Adapter for NapcatAdapter {
+    fn run(self: Arc<Self>) {
+        let arc_self = self.clone();
+        let fut = async move {
+            arc_self.clone().connect().await;
+        };
+        tokio::spawn(fut);
+        self.run_detached();
+    }
+
+    fn get_event_port(&self) -> SignalPort<Value> {
+        self.ws_event_hub.get_port()
+    }
+
+    fn get_action_port(&self) -> SignalPort<Value> {
+        self.ws_action_hub.get_port()
+    }
+
+    fn get_state_port(&self) -> SignalPort<ConnectionEvent> {
+        self.state_hub.get_port()
+    }
+}
+
+/// 断线重连的指数退避: 从 initial 开始每次失败后翻倍, 不超过 max; 连接成功后复位
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max: max.max(initial),
+            current: initial,
+        }
+    }
+
+    pub fn from_config() -> Self {
+        let config = Config::get_or_init();
+        Self::new(
+            Duration::from_millis(*config.reconnect_interval()),
+            Duration::from_millis(*config.reconnect_max_interval()),
+        )
+    }
+
+    /// 本次应等待的时间; 下一次的间隔随之翻倍
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+/// 一条 Napcat 连接; 所属账号在握手或首个上报中确定
+struct Session {
+    self_id: Option<i64>,
+    tx: mpsc::UnboundedSender<Value>,
 }
 
 impl NapcatAdapter {
     pub fn new() -> Arc<Self> {
+        Self::with_options(NapcatOptions::from_config())
+    }
+
+    pub fn with_options(options: NapcatOptions) -> Arc<Self> {
         Arc::new(Self {
             ws_event_hub: Arc::new(SignalHub::new()),
             ws_action_hub: Arc::new(SignalHub::new()),
+            state_hub: Arc::new(SignalHub::new()),
+            quick_slots: DashMap::new(),
+            connections: DashMap::new(),
+            options,
         })
     }
 
-    pub fn run(self: &Arc<Self>) {
-        let arc_self = self.clone();
-        let fut = async move {
-            arc_self.clone().connect().await;
-        };
-        tokio::spawn(fut);
+    /// 只启动动作路由, 不监听端口也不主动连接; 连接经由 attach 接入
+    pub fn run_detached(self: Arc<Self>) {
+        tokio::spawn(async move { self.route_actions().await });
     }
 
-    pub fn get_event_port(&self) -> SignalPort<Value> {
-        self.ws_event_hub.get_port()
+    /// 接管一条已建立的 WebSocket 连接 (如自定义传输), 直到连接断开;
+    /// 所属账号由收到的首个带 self_id 的数据确定
+    pub async fn attach<S>(&self, stream: tokio_tungstenite::WebSocketStream<S>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        self.handle_stream(stream, None).await;
     }
 
-    pub fn get_action_port(&self) -> SignalPort<Value> {
-        self.ws_action_hub.get_port()
+    /// 反向 WS 时本地同时提供 `/ws` 与 `/webhook`;
+    /// 正向 WS 时主动连接 Napcat, 本地仍提供 `/webhook` 接收 HTTP 上报
+    async fn connect(self: Arc<Self>) {
+        match Config::get_or_init().websocket_mode() {
+            WebSocketMode::Reverse => Self::serve(self.router()).await,
+            WebSocketMode::Forward => {
+                tokio::spawn(Self::serve(self.clone().webhook_router()));
+                self.connect_forward().await
+            }
+        }
     }
 
-    async fn connect(&self) {
+    /// 在 websocket_addr 上提供本地服务; 监听失败时只记录错误
+    async fn serve(router: Router<()>) {
         let addr = Config::get_or_init().websocket_addr();
-        let m_state = (self.ws_event_hub.clone(), self.ws_action_hub.clone());
-        type MyState = State<(Arc<SignalHub<Value>>, Arc<SignalHub<Value>>)>;
-        let router: Router<()> = Router::new()
-            .route(
-                "/ws",
-                any(
-                    move |ws: WebSocketUpgrade, State(hubs): MyState| async move {
-                        ws.on_upgrade(move |ws: WebSocket| async move {
-                            Self::handle_socket(ws, hubs).await
-                        })
-                    },
-                ),
-            )
-            .with_state(m_state);
-        let listener = tokio::net::TcpListener::bind(addr.clone()).await.unwrap();
+        let listener = match tokio::net::TcpListener::bind(addr.clone()).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("[初始化] 无法监听 {}: {}", addr, e);
+                return;
+            }
+        };
         tracing::info!("[初始化] 成功启用服务 {}", addr);
-        axum::serve(listener, router).await.unwrap();
+        let service = router.into_make_service_with_connect_info::<SocketAddr>();
+        if let Err(e) = axum::serve(listener, service).await {
+            tracing::error!("[初始化] 服务 {} 异常退出: {}", addr, e);
+        }
     }
 
-    async fn handle_socket(
-        mut ws: WebSocket,
-        hubs: (Arc<SignalHub<Value>>, Arc<SignalHub<Value>>),
-    ) {
-        let ws_event_hub = hubs.0.clone();
-        let ws_action_hub = hubs.1.clone();
+    /// 反向 WS (`/ws`) 与 HTTP 上报 (`/webhook`) 的路由;
+    /// 需以 into_make_service_with_connect_info::<SocketAddr> 提供服务
+    pub fn router(self: Arc<Self>) -> Router<()> {
+        Self::ws_routes()
+            .merge(Self::webhook_routes())
+            .with_state(self)
+    }
+
+    /// 仅 HTTP 上报 (`/webhook`) 的路由, 用于正向 WS 模式
+    pub fn webhook_router(self: Arc<Self>) -> Router<()> {
+        Self::webhook_routes().with_state(self)
+    }
+
+    fn webhook_routes() -> Router<Arc<Self>> {
+        Router::new().route("/webhook", post(Self::handle_webhook))
+    }
 
+    fn ws_routes() -> Router<Arc<Self>> {
+        Router::new().route(
+            "/ws",
+            any(
+                move |ws: WebSocketUpgrade,
+                      headers: HeaderMap,
+                      Query(query): Query<HashMap<String, String>>,
+                      ConnectInfo(peer): ConnectInfo<SocketAddr>,
+                      State(adapter): State<Arc<Self>>| async move {
+                    let token = &adapter.options.access_token;
+                    if !Self::check_access_token(&headers, &query, token) {
+                        tracing::warn!("[鉴权] 拒绝未授权的连接 {}", peer);
+                        return StatusCode::UNAUTHORIZED.into_response();
+                    }
+                    let self_id = headers
+                        .get("X-Self-ID")
+                        .and_then(|id| id.to_str().ok())
+                        .and_then(|id| id.parse::<i64>().ok());
+                    ws.on_upgrade(move |ws: WebSocket| async move {
+                        adapter.handle_socket(ws, self_id).await
+                    })
+                },
+            ),
+        )
+    }
+
+    /// 校验 OneBot 鉴权: `Authorization: Bearer <token>` 或 `?access_token=<token>`
+    fn check_access_token(
+        headers: &HeaderMap,
+        query: &HashMap<String, String>,
+        token: &str,
+    ) -> bool {
+        if token.is_empty() {
+            return true;
+        }
+        let header_token = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|value| value.trim());
+        let query_token = query.get("access_token").map(|value| value.as_str());
+        let header_ok = header_token.is_some_and(|given| Self::token_matches(given, token));
+        let query_ok = query_token.is_some_and(|given| Self::token_matches(given, token));
+        header_ok || query_ok
+    }
+
+    /// 以 token 为密钥比较两者的 HMAC, verify_slice 为常数时间比较, 不泄露匹配的前缀长度
+    fn token_matches(given: &str, token: &str) -> bool {
+        let Ok(mut expected) = Hmac::<Sha1>::new_from_slice(token.as_bytes()) else {
+            return false;
+        };
+        expected.update(token.as_bytes());
+        let expected = expected.finalize().into_bytes();
+        let Ok(mut mac) = Hmac::<Sha1>::new_from_slice(token.as_bytes()) else {
+            return false;
+        };
+        mac.update(given.as_bytes());
+        mac.verify_slice(&expected).is_ok()
+    }
+
+    /// 接收 Napcat 的 HTTP POST 上报, 并在短时间内等待插件给出快速操作作为响应体
+    async fn handle_webhook(
+        State(adapter): State<Arc<Self>>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Response {
+        let secret = &adapter.options.http_secret;
+        if !secret.is_empty() && !Self::check_signature(&headers, &body, secret) {
+            tracing::warn!("[鉴权] 拒绝签名错误的上报 {}", peer);
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        let Ok(value) = serde_json::from_slice::<Value>(&body) else {
+            tracing::warn!(
+                "[数据异常] 上报转化Value失败: {}",
+                String::from_utf8_lossy(&body)
+            );
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+        let quick_slot = match value["post_type"].as_str() {
+            Some("message") | Some("request") => {
+                let key = Self::quick_operation_key(&value);
+                let (tx, rx) = oneshot::channel();
+                adapter.quick_slots.insert(key.clone(), tx);
+                Some((key, rx))
+            }
+            _ => None,
+        };
+        let _ = adapter.ws_event_hub.send(value);
+        let Some((key, rx)) = quick_slot else {
+            return StatusCode::NO_CONTENT.into_response();
+        };
+        match tokio::time::timeout(adapter.options.quick_timeout, rx).await {
+            Ok(Ok(operation)) => Json(operation).into_response(),
+            _ => {
+                adapter.quick_slots.remove(&key);
+                StatusCode::NO_CONTENT.into_response()
+            }
+        }
+    }
+
+    /// 校验 `X-Signature: sha1=<hex>`, 即以密钥对请求体做 HMAC-SHA1
+    fn check_signature(headers: &HeaderMap, body: &[u8], secret: &str) -> bool {
+        let Some(signature) = headers
+            .get("X-Signature")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("sha1="))
+            .and_then(|value| hex::decode(value).ok())
+        else {
+            return false;
+        };
+        let Ok(mut mac) = Hmac::<Sha1>::new_from_slice(secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// 快速操作以触发事件为上下文, 用 self_id 与 message_id/flag 定位等待中的上报
+    fn quick_operation_key(context: &Value) -> String {
+        let id = context
+            .get("message_id")
+            .or(context.get("flag"))
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+        format!("{}:{}", context["self_id"], id)
+    }
+
+    /// 按 self_id 将 ActionManager 发出的动作交给对应账号的连接;
+    /// 能被 HTTP 上报响应承接的快速操作在此截获
+    async fn route_actions(&self) {
         loop {
+            let Some(mut value) = self.ws_action_hub.recv().await else {
+                continue;
+            };
+            if value["action"] == QUICK_OPERATION_ACTION && self.try_quick_operation(&value) {
+                continue;
+            }
+            let self_id = value
+                .as_object_mut()
+                .and_then(|value| value.remove("self_id"))
+                .and_then(|id| id.as_i64());
+            match self.route(self_id) {
+                Ok(tx) => {
+                    let _ = tx.send(value);
+                }
+                Err((retcode, message)) => {
+                    tracing::warn!("[路由] 账号 {:?}: {}", self_id, message);
+                    self.reply_echo(&value, "failed", retcode, message);
+                }
+            }
+        }
+    }
+
+    /// 选择动作的出口: 指定账号或默认账号的连接; 未指定且有多条连接时无法确定发送账号
+    fn route(
+        &self,
+        self_id: Option<i64>,
+    ) -> Result<mpsc::UnboundedSender<Value>, (i64, &'static str)> {
+        match self_id.or(self.options.default_account) {
+            Some(self_id) => self
+                .connections
+                .get(&self_id)
+                .map(|tx| tx.clone())
+                .ok_or((1404, "no connection for account")),
+            None if self.connections.len() > 1 => Err((1400, "ambiguous account")),
+            None => self
+                .connections
+                .iter()
+                .next()
+                .map(|tx| tx.value().clone())
+                .ok_or((1404, "no connection for account")),
+        }
+    }
+
+    fn try_quick_operation(&self, value: &Value) -> bool {
+        let key = Self::quick_operation_key(&value["params"]["context"]);
+        if let Some((_, tx)) = self.quick_slots.remove(&key)
+            && tx.send(value["params"]["operation"].clone()).is_ok()
+        {
+            self.reply_echo(value, "ok", 0, "");
+            return true;
+        }
+        if self.connections.is_empty() {
+            tracing::warn!("[快速操作] 上报已响应且无可用连接, 丢弃");
+            self.reply_echo(value, "failed", 1400, "report already answered");
+            return true;
+        }
+        false
+    }
+
+    fn reply_echo(&self, value: &Value, status: &str, retcode: i64, message: &str) {
+        let _ = self.ws_action_hub.send(json!({
+            "status": status,
+            "retcode": retcode,
+            "data": null,
+            "message": message,
+            "wording": message,
+            "echo": value["echo"],
+        }));
+    }
+
+    fn open_session(&self, self_id: Option<i64>) -> (Session, mpsc::UnboundedReceiver<Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut session = Session { self_id: None, tx };
+        if let Some(self_id) = self_id {
+            self.bind_session(&mut session, self_id);
+        }
+        (session, rx)
+    }
+
+    /// 将连接登记为 self_id 的动作出口
+    fn bind_session(&self, session: &mut Session, self_id: i64) {
+        if session.self_id == Some(self_id) {
+            return;
+        }
+        if let Some(old_id) = session.self_id {
+            self.connections
+                .remove_if(&old_id, |_, tx| tx.same_channel(&session.tx));
+            self.notify_lifecycle(old_id, "disable");
+            self.publish_state(ConnectionEvent::disconnected(old_id, "account changed"));
+        }
+        self.connections.insert(self_id, session.tx.clone());
+        session.self_id = Some(self_id);
+        // 每次 (重新) 连接都通知插件, 与断开时的 disable 对应
+        self.notify_lifecycle(self_id, "enable");
+        self.publish_state(ConnectionEvent::connected(self_id));
+    }
+
+    fn close_session(&self, session: Session, reason: String) {
+        tracing::warn!(
+            "[断开] Napcat 连接已断开 [id = {:?}]: {}",
+            session.self_id,
+            reason
+        );
+        let Some(self_id) = session.self_id else {
+            return;
+        };
+        self.connections
+            .remove_if(&self_id, |_, tx| tx.same_channel(&session.tx));
+        self.notify_lifecycle(self_id, "disable");
+        self.publish_state(ConnectionEvent::disconnected(self_id, reason));
+    }
+
+    /// 正向 WS: 为每个配置的地址各维持一条连接
+    async fn connect_forward(self: Arc<Self>) {
+        let tasks: Vec<_> = Config::get_or_init()
+            .napcat_websocket_urls()
+            .iter()
+            .map(|url| tokio::spawn(self.clone().connect_forward_to(url.clone())))
+            .collect();
+        futures_util::future::join_all(tasks).await;
+    }
+
+    /// 主动连接一个 Napcat, 断线后按指数退避重连; 握手时携带 access_token
+    pub async fn connect_forward_to(self: Arc<Self>, url: String) {
+        let mut backoff = Backoff::from_config();
+        loop {
+            match Self::build_forward_request(&url, &self.options.access_token) {
+                Ok(request) => match tokio_tungstenite::connect_async(request).await {
+                    Ok((stream, _)) => {
+                        tracing::info!("[连接] 已连接 Napcat {}", url);
+                        backoff.reset();
+                        self.handle_stream(stream, None).await;
+                    }
+                    Err(e) => {
+                        tracing::warn!("[连接] 连接 Napcat 失败 {}: {}", url, e);
+                    }
+                },
+                Err(e) => {
+                    tracing::error!("[连接] 无效的 Napcat 地址 {}: {}", url, e);
+                    return;
+                }
+            }
+            let delay = backoff.next_delay();
+            tracing::info!("[重连] {:?} 后重试 {}", delay, url);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// 正向连接的握手请求, token 非空时附带 `Authorization: Bearer <token>`
+    pub fn build_forward_request(
+        url: &str,
+        token: &str,
+    ) -> Result<tungstenite::handshake::client::Request, tungstenite::Error> {
+        let mut request = url.into_client_request()?;
+        if !token.is_empty() {
+            let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|e| tungstenite::Error::HttpFormat(e.into()))?;
+            request.headers_mut().insert("Authorization", value);
+        }
+        Ok(request)
+    }
+
+    /// 驱动一条正向连接直到断开
+    async fn handle_stream<S>(
+        &self,
+        stream: tokio_tungstenite::WebSocketStream<S>,
+        self_id: Option<i64>,
+    ) where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        let (mut session, mut rx) = self.open_session(self_id);
+        let (mut sink, mut stream) = stream.split();
+        let reason = loop {
+            tokio::select! {
+                res = stream.next() => {
+                    let msg = match res {
+                        Some(Ok(msg)) => msg,
+                        Some(Err(e)) => break e.to_string(),
+                        None => break "connection closed".to_string(),
+                    };
+                    match msg {
+                        tungstenite::Message::Text(text) => {
+                            if let Some(self_id) = self.dispatch_incoming(&text) {
+                                self.bind_session(&mut session, self_id);
+                            }
+                        }
+                        tungstenite::Message::Close(frame) => {
+                            break frame
+                                .map(|frame| frame.reason.to_string())
+                                .unwrap_or("close frame".to_string());
+                        }
+                        _ => {}
+                    }
+                },
+                res = rx.recv() => {
+                    let Some(response) = res else {
+                        continue;
+                    };
+                    let msg = tungstenite::Message::Text(response.to_string().into());
+                    if let Err(e) = sink.send(msg).await {
+                        break e.to_string();
+                    }
+                }
+            }
+        };
+        self.close_session(session, reason);
+    }
+
+    /// 连接状态变化时向事件流注入一条 lifecycle 元事件
+    fn notify_lifecycle(&self, self_id: i64, sub_type: &str) {
+        let value = json!({
+            "post_type": "meta_event",
+            "meta_event_type": "lifecycle",
+            "sub_type": sub_type,
+            "self_id": self_id,
+            "time": chrono::Utc::now().timestamp(),
+        });
+        let _ = self.ws_event_hub.send(value);
+    }
+
+    fn publish_state(&self, event: ConnectionEvent) {
+        let _ = self.state_hub.send(event);
+    }
+
+    /// 分发收到的数据, 返回其中携带的 self_id
+    fn dispatch_incoming(&self, text: &str) -> Option<i64> {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+            tracing::warn!("[数据异常] 转化Value失败: {}", text);
+            return None;
+        };
+
+        if value.get("echo").is_some() {
+            let _ = self.ws_action_hub.send(value);
+            None
+        } else {
+            let self_id = value["self_id"].as_i64().or_else(|| {
+                value["self"]["user_id"]
+                    .as_str()
+                    .and_then(|id| id.parse::<i64>().ok())
+            });
+            let _ = self.ws_event_hub.send(value);
+            self_id
+        }
+    }
+
+    /// 驱动一条反向连接直到断开
+    async fn handle_socket(&self, mut ws: WebSocket, self_id: Option<i64>) {
+        let (mut session, mut rx) = self.open_session(self_id);
+        let reason = loop {
             tokio::select! {
                 res = ws.recv() => {
-                    let response = res.and_then(|opt| {opt.ok()});
-                    let Some(response) = response else {
-                            tracing::warn!("[接收] 网络读取异常");
-                            continue;
+                    let response = match res {
+                        Some(Ok(response)) => response,
+                        Some(Err(e)) => break e.to_string(),
+                        None => break "connection closed".to_string(),
                     };
 
+                    if let Message::Close(frame) = response {
+                        break frame
+                            .map(|frame| frame.reason.to_string())
+                            .unwrap_or("close frame".to_string());
+                    }
+
                     let Ok(response) = response.to_text() else{
                         tracing::warn!("[数据跳过] 可能接受了非文本类型");
                         continue
                     };
 
-                    let Ok(value) = serde_json::from_str::<serde_json::Value>(response) else {
-                        tracing::warn!("[数据异常] 转化Value失败: {}", response);
-                        continue;
-                    };
-
-                    if value.get("echo").is_some() {
-                        let _ = ws_action_hub.send(value);
-                    } else {
-                        let _ = ws_event_hub.send(value);
+                    if let Some(self_id) = self.dispatch_incoming(response) {
+                        self.bind_session(&mut session, self_id);
                     }
                 },
-                res = ws_event_hub.recv() => {
+                res = self.ws_event_hub.recv() => {
                     let Some(response) = res else {
                         continue;
                     };
@@ -101,17 +623,22 @@ impl NapcatAdapter {
                         continue;
                     };
                     let msg = Message::Text(response.into());
-                    let _ = ws.send(msg).await;
+                    if let Err(e) = ws.send(msg).await {
+                        break e.to_string();
+                    }
                 },
-                res = ws_action_hub.recv() => {
+                res = rx.recv() => {
                     let Some(response) = res else {
                         continue;
                     };
                     let response = response.to_string();
                     let msg = Message::Text(response.into());
-                    let _ = ws.send(msg).await;
+                    if let Err(e) = ws.send(msg).await {
+                        break e.to_string();
+                    }
                 }
             }
-        }
+        };
+        self.close_session(session, reason);
     }
 }
@@ -0,0 +1,302 @@
+use crate::{
+    config::Config,
+    core::{action::ActionManager, event::EventNexus},
+    types::{
+        action_type::{
+            ActionError,
+            response::{FriendInfo, GroupInfo, GroupMemberInfo},
+        },
+        event_type::notice_event::{NoticeEvent, NotifyEvent},
+    },
+};
+use dashmap::DashMap;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// 带获取时间的缓存项
+struct Entry<T> {
+    value: T,
+    fetched: Instant,
+}
+
+type Slot<T> = Arc<Mutex<Option<Entry<T>>>>;
+
+/// 单个账号的缓存; 各列表首次查询时整体拉取
+#[derive(Default)]
+struct AccountCache {
+    friends: Slot<HashMap<i64, FriendInfo>>,
+    groups: Slot<HashMap<i64, GroupInfo>>,
+    members: DashMap<i64, Slot<HashMap<i64, GroupMemberInfo>>>,
+}
+
+impl AccountCache {
+    fn members(&self, group_id: i64) -> Slot<HashMap<i64, GroupMemberInfo>> {
+        self.members.entry(group_id).or_default().clone()
+    }
+}
+
+/// 好友、群与群成员信息缓存: 按需拉取, 由通知事件更新, 超过 TTL 后重新拉取
+pub struct InfoCache {
+    ttl: Duration,
+    /// 账号 -> 缓存; None 为未指定账号的 ActionManager 使用
+    accounts: DashMap<Option<i64>, Arc<AccountCache>>,
+}
+
+impl InfoCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            accounts: DashMap::new(),
+        }
+    }
+
+    pub fn from_config() -> Self {
+        Self::new(Duration::from_secs(*Config::get_or_init().cache_ttl()))
+    }
+
+    fn account(&self, self_id: Option<i64>) -> Arc<AccountCache> {
+        self.accounts.entry(self_id).or_default().clone()
+    }
+
+    /// 通知可能来自任一账号, 同时作用于该账号与未指定账号的缓存
+    fn accounts_for(&self, self_id: i64) -> Vec<Arc<AccountCache>> {
+        [Some(self_id), None]
+            .into_iter()
+            .filter_map(|id| self.accounts.get(&id).map(|cache| cache.clone()))
+            .collect()
+    }
+
+    /// 读取未过期的缓存, 否则先调用 fetch 拉取并写入
+    async fn read<T, R, F, Fut>(
+        &self,
+        slot: &Slot<T>,
+        fetch: F,
+        read: impl FnOnce(&T) -> R,
+    ) -> Result<R, ActionError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ActionError>>,
+    {
+        // 持锁拉取, 并发查询只会触发一次请求
+        let mut slot = slot.lock().await;
+        if let Some(entry) = slot.as_ref()
+            && entry.fetched.elapsed() < self.ttl
+        {
+            return Ok(read(&entry.value));
+        }
+        let entry = slot.insert(Entry {
+            value: fetch().await?,
+            fetched: Instant::now(),
+        });
+        Ok(read(&entry.value))
+    }
+
+    async fn update<T>(slot: &Slot<T>, f: impl FnOnce(&mut T)) {
+        if let Some(entry) = slot.lock().await.as_mut() {
+            f(&mut entry.value);
+        }
+    }
+
+    pub async fn friends<R>(
+        &self,
+        act: &ActionManager,
+        read: impl FnOnce(&HashMap<i64, FriendInfo>) -> R,
+    ) -> Result<R, ActionError> {
+        let account = self.account(act.self_id());
+        let fetch = || async {
+            let list = act.get_friend_list().await?;
+            Ok(list.into_iter().map(|info| (info.user_id, info)).collect())
+        };
+        self.read(&account.friends, fetch, read).await
+    }
+
+    pub async fn groups<R>(
+        &self,
+        act: &ActionManager,
+        read: impl FnOnce(&HashMap<i64, GroupInfo>) -> R,
+    ) -> Result<R, ActionError> {
+        let account = self.account(act.self_id());
+        let fetch = || async {
+            let list = act.get_group_list().await?;
+            Ok(list.into_iter().map(|info| (info.group_id, info)).collect())
+        };
+        self.read(&account.groups, fetch, read).await
+    }
+
+    pub async fn members<R>(
+        &self,
+        act: &ActionManager,
+        group_id: i64,
+        read: impl FnOnce(&HashMap<i64, GroupMemberInfo>) -> R,
+    ) -> Result<R, ActionError> {
+        let slot = self.account(act.self_id()).members(group_id);
+        let fetch = || async {
+            let list = act.get_group_member_list(group_id).await?;
+            Ok(list.into_iter().map(|info| (info.user_id, info)).collect())
+        };
+        self.read(&slot, fetch, read).await
+    }
+
+    /// 丢弃某账号的全部缓存
+    pub fn clear(&self, self_id: Option<i64>) {
+        self.accounts.remove(&self_id);
+    }
+
+    /// 根据通知事件更新缓存
+    pub async fn apply_notice(&self, notice: &NoticeEvent) {
+        let Some(self_id) = notice.self_id() else {
+            return;
+        };
+        for account in self.accounts_for(self_id) {
+            match notice {
+                NoticeEvent::GroupIncrease(e) if e.user_id == self_id => {
+                    *account.groups.lock().await = None;
+                }
+                NoticeEvent::GroupIncrease(e) => {
+                    *account.members(e.group_id).lock().await = None;
+                    Self::update(&account.groups, |groups| {
+                        if let Some(group) = groups.get_mut(&e.group_id) {
+                            group.member_count += 1;
+                        }
+                    })
+                    .await;
+                }
+                NoticeEvent::GroupDecrease(e)
+                    if e.sub_type == "kick_me" || e.user_id == self_id =>
+                {
+                    account.members.remove(&e.group_id);
+                    Self::update(&account.groups, |groups| {
+                        groups.remove(&e.group_id);
+                    })
+                    .await;
+                }
+                NoticeEvent::GroupDecrease(e) => {
+                    Self::update(&account.members(e.group_id), |members| {
+                        members.remove(&e.user_id);
+                    })
+                    .await;
+                    Self::update(&account.groups, |groups| {
+                        if let Some(group) = groups.get_mut(&e.group_id) {
+                            group.member_count -= 1;
+                        }
+                    })
+                    .await;
+                }
+                NoticeEvent::GroupCard(e) => {
+                    Self::update(&account.members(e.group_id), |members| {
+                        if let Some(member) = members.get_mut(&e.user_id) {
+                            member.card = e.card_new.clone();
+                        }
+                    })
+                    .await;
+                }
+                NoticeEvent::GroupAdmin(e) => {
+                    let role = if e.sub_type == "set" {
+                        "admin"
+                    } else {
+                        "member"
+                    };
+                    Self::update(&account.members(e.group_id), |members| {
+                        if let Some(member) = members.get_mut(&e.user_id) {
+                            member.role = role.to_string();
+                        }
+                    })
+                    .await;
+                }
+                NoticeEvent::Notify(NotifyEvent::GroupName(e)) => {
+                    Self::update(&account.groups, |groups| {
+                        if let Some(group) = groups.get_mut(&e.group_id) {
+                            group.group_name = e.name_new.clone();
+                        }
+                    })
+                    .await;
+                }
+                NoticeEvent::Notify(NotifyEvent::Title(e)) => {
+                    Self::update(&account.members(e.group_id), |members| {
+                        if let Some(member) = members.get_mut(&e.user_id) {
+                            member.title = e.title.clone();
+                        }
+                    })
+                    .await;
+                }
+                NoticeEvent::FriendAdd(_) => {
+                    *account.friends.lock().await = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 监听通知事件以更新缓存
+    pub fn run(self: Arc<Self>, event_nexus: Arc<EventNexus>) {
+        let notice_port = event_nexus.get_notice_port();
+        tokio::spawn(async move {
+            loop {
+                let Ok(notice) = notice_port.recv().await else {
+                    continue;
+                };
+                self.apply_notice(&notice).await;
+            }
+        });
+    }
+}
+
+impl Default for InfoCache {
+    fn default() -> Self {
+        Self::from_config()
+    }
+}
+
+/// 经由缓存的查询, 以 ActionManager 的账号区分缓存
+impl ActionManager {
+    pub async fn cached_friend(&self, user_id: i64) -> Result<Option<FriendInfo>, ActionError> {
+        let read = |friends: &HashMap<i64, FriendInfo>| friends.get(&user_id).cloned();
+        self.cache().friends(self, read).await
+    }
+
+    pub async fn cached_friend_list(&self) -> Result<Vec<FriendInfo>, ActionError> {
+        let read = |friends: &HashMap<i64, FriendInfo>| friends.values().cloned().collect();
+        self.cache().friends(self, read).await
+    }
+
+    pub async fn cached_group(&self, group_id: i64) -> Result<Option<GroupInfo>, ActionError> {
+        let read = |groups: &HashMap<i64, GroupInfo>| groups.get(&group_id).cloned();
+        self.cache().groups(self, read).await
+    }
+
+    pub async fn cached_group_list(&self) -> Result<Vec<GroupInfo>, ActionError> {
+        let read = |groups: &HashMap<i64, GroupInfo>| groups.values().cloned().collect();
+        self.cache().groups(self, read).await
+    }
+
+    pub async fn cached_member(
+        &self,
+        group_id: i64,
+        user_id: i64,
+    ) -> Result<Option<GroupMemberInfo>, ActionError> {
+        let read = |members: &HashMap<i64, GroupMemberInfo>| members.get(&user_id).cloned();
+        self.cache().members(self, group_id, read).await
+    }
+
+    pub async fn cached_member_list(
+        &self,
+        group_id: i64,
+    ) -> Result<Vec<GroupMemberInfo>, ActionError> {
+        let read = |members: &HashMap<i64, GroupMemberInfo>| members.values().cloned().collect();
+        self.cache().members(self, group_id, read).await
+    }
+
+    /// 成员的显示名: 群名片, 为空时为昵称
+    pub async fn cached_display_name(&self, group_id: i64, user_id: i64) -> Option<String> {
+        let member = self.cached_member(group_id, user_id).await.ok()??;
+        Some(if member.card.is_empty() {
+            member.nickname
+        } else {
+            member.card
+        })
+    }
+}
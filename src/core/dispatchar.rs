@@ -0,0 +1,258 @@
+use crate::{
+    core::{action::ActionManager, event::EventNexus},
+    types::event_type::message_event::MessageEvent,
+};
+use regex::Regex;
+use std::{
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+use tokio::sync::{broadcast::error::RecvError, watch};
+
+/// 未指定时的优先级; 数值越小越先执行
+pub const DEFAULT_PRIORITY: i32 = 50;
+
+type HandlerFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type HandlerFn = Arc<dyn Fn(Arc<MessageEvent>, Arc<ActionManager>) -> HandlerFuture + Send + Sync>;
+type Predicate = Arc<dyn Fn(&MessageEvent) -> bool + Send + Sync>;
+
+/// 消息的匹配条件; 命令、前缀、正则与关键词均作用于去掉首尾空白的纯文本
+#[derive(Clone)]
+pub enum Matcher {
+    /// 第一个词与命令完全相同, 如 "/mood" 匹配 "/mood" 与 "/mood now"
+    Command(String),
+    Prefix(String),
+    Regex(Regex),
+    Keyword(String),
+    /// 消息中 @ 了机器人
+    AtBot,
+    /// 私聊, 或群聊中 @ 了机器人
+    ToMe,
+    Private,
+    Group,
+    /// 仅来自这些群的消息
+    Groups(Vec<i64>),
+    /// 仅来自这些用户的消息
+    Users(Vec<i64>),
+    Custom(Predicate),
+}
+
+impl Matcher {
+    pub fn command(command: impl Into<String>) -> Self {
+        Self::Command(command.into())
+    }
+
+    pub fn prefix(prefix: impl Into<String>) -> Self {
+        Self::Prefix(prefix.into())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Self::Regex)
+    }
+
+    pub fn keyword(keyword: impl Into<String>) -> Self {
+        Self::Keyword(keyword.into())
+    }
+
+    pub fn custom(predicate: impl Fn(&MessageEvent) -> bool + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(predicate))
+    }
+
+    pub fn matches(&self, event: &MessageEvent) -> bool {
+        match self {
+            Self::Command(command) => {
+                event.plain_text().split_whitespace().next() == Some(command.as_str())
+            }
+            Self::Prefix(prefix) => event.plain_text().starts_with(prefix.as_str()),
+            Self::Regex(regex) => regex.is_match(&event.plain_text()),
+            Self::Keyword(keyword) => event.plain_text().contains(keyword.as_str()),
+            Self::AtBot => event.is_at_self(),
+            Self::ToMe => matches!(event, MessageEvent::Private(_)) || event.is_at_self(),
+            Self::Private => matches!(event, MessageEvent::Private(_)),
+            Self::Group => matches!(event, MessageEvent::Group(_)),
+            Self::Groups(groups) => event.group_id().is_some_and(|id| groups.contains(&id)),
+            Self::Users(users) => users.contains(&event.sender().user_id),
+            Self::Custom(predicate) => predicate(event),
+        }
+    }
+}
+
+/// 消息处理器: 所有匹配条件都满足时执行
+#[derive(Clone)]
+pub struct Handler {
+    name: String,
+    matchers: Vec<Matcher>,
+    priority: i32,
+    block: bool,
+    dry_run: bool,
+    func: HandlerFn,
+}
+
+impl Handler {
+    pub fn new<F, Fut>(name: impl Into<String>, func: F) -> Self
+    where
+        F: Fn(Arc<MessageEvent>, Arc<ActionManager>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            matchers: Vec::new(),
+            priority: DEFAULT_PRIORITY,
+            block: false,
+            dry_run: false,
+            func: Arc::new(move |event, act| Box::pin(func(event, act))),
+        }
+    }
+
+    pub fn with_matcher(mut self, matcher: Matcher) -> Self {
+        self.matchers.push(matcher);
+        self
+    }
+
+    /// 数值越小越先执行
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 命中后不再执行优先级更低的处理器, 同优先级的仍会执行
+    pub fn with_block(mut self, block: bool) -> Self {
+        self.block = block;
+        self
+    }
+
+    /// 以演练模式执行: 写操作只记录日志而不发出
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn is_block(&self) -> bool {
+        self.block
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn matches(&self, event: &MessageEvent) -> bool {
+        self.matchers.iter().all(|matcher| matcher.matches(event))
+    }
+}
+
+/// 消息分发器: 按优先级依次执行命中的处理器
+pub struct Dispatcher {
+    /// 按优先级排序, 同优先级按注册顺序
+    handlers: RwLock<Vec<Arc<Handler>>>,
+    /// 置为 true 后 run 启动的监听任务退出
+    shutdown: watch::Sender<bool>,
+}
+
+impl Default for Dispatcher {
+    fn default() -> Self {
+        Self {
+            handlers: RwLock::new(Vec::new()),
+            shutdown: watch::Sender::new(false),
+        }
+    }
+}
+
+impl Dispatcher {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn register(&self, handler: Handler) {
+        tracing::info!(
+            "[Dispatch] 注册处理器 {} [priority = {}]",
+            handler.name,
+            handler.priority
+        );
+        let mut handlers = self.handlers.write().unwrap();
+        let index = handlers.partition_point(|h| h.priority <= handler.priority);
+        handlers.insert(index, Arc::new(handler));
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 依次执行命中的处理器, 返回执行过的处理器名称
+    pub async fn dispatch(&self, event: Arc<MessageEvent>, act: Arc<ActionManager>) -> Vec<String> {
+        let handlers = self.handlers.read().unwrap().clone();
+        let mut blocked_at = None;
+        let mut handled = Vec::new();
+        for handler in handlers {
+            if blocked_at.is_some_and(|priority| handler.priority > priority) {
+                break;
+            }
+            if !handler.matches(&event) {
+                continue;
+            }
+            tracing::debug!("[Dispatch] {} 处理消息", handler.name);
+            let act = if handler.dry_run {
+                act.dry_run()
+            } else {
+                act.clone()
+            };
+            (handler.func)(event.clone(), act).await;
+            handled.push(handler.name.clone());
+            if handler.block {
+                blocked_at = Some(handler.priority);
+            }
+        }
+        handled
+    }
+
+    /// 停止 run 启动的监听; 已在分发中的消息仍会执行完
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// 监听私聊与群消息, 每条消息在独立的任务中分发; 调用 shutdown 后退出
+    pub fn run(self: Arc<Self>, event_nexus: Arc<EventNexus>, act: Arc<ActionManager>) {
+        let private_port = event_nexus.get_private_message_port();
+        let group_port = event_nexus.get_group_message_port();
+        let mut shutdown = self.shutdown.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    _ = shutdown.wait_for(|stop| *stop) => break,
+                    msg = private_port.recv() => msg.map(|msg| MessageEvent::Private((*msg).clone())),
+                    msg = group_port.recv() => msg.map(|msg| MessageEvent::Group((*msg).clone())),
+                };
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("[Dispatch] 处理过慢, 跳过 {} 条消息", skipped);
+                        continue;
+                    }
+                    // 端口自身持有发送端, 实际不会关闭
+                    Err(RecvError::Closed) => break,
+                };
+                let dispatcher = self.clone();
+                let act = act.clone();
+                tokio::spawn(async move {
+                    dispatcher.dispatch(Arc::new(event), act).await;
+                });
+            }
+        });
+    }
+}
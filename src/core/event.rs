@@ -1,8 +1,16 @@
 use crate::types::{
     event_type::{
         AnyEvent,
+        connection_event::{ConnectionEvent, ConnectionState},
         message_event::{GroupMessageEvent, MessageEvent, PrivateMessageEvent},
         meta_event::{HeartBeatEvent, LifeCycleEvent, MetaEvent},
+        notice_event::{
+            EssenceNotice, FriendAddNotice, FriendRecallNotice, GroupAdminNotice, GroupBanNotice,
+            GroupCardNotice, GroupDecreaseNotice, GroupIncreaseNotice, GroupMsgEmojiLikeNotice,
+            GroupNameNotify, GroupRecallNotice, GroupUploadNotice, HonorNotify, InputStatusNotify,
+            LuckyKingNotify, NoticeEvent, NotifyEvent, PokeNotify, TitleNotify,
+        },
+        request_event::{FriendRequestEvent, GroupRequestEvent, RequestEvent},
     },
     signal_type::{SignalHub, SignalPort},
 };
@@ -11,21 +19,42 @@ use std::sync::Arc;
 
 pub struct EventManager {
     ws_port: SignalPort<Value>,
+    state_port: SignalPort<ConnectionEvent>,
     hubs: EventHubs,
 }
 
 impl EventManager {
-    pub fn new(ws_port: SignalPort<Value>) -> Arc<Self> {
+    pub fn new(ws_port: SignalPort<Value>, state_port: SignalPort<ConnectionEvent>) -> Arc<Self> {
         Arc::new(Self {
             ws_port,
+            state_port,
             hubs: EventHubs::new(),
         })
     }
 
+    async fn handle_state(&self) -> Result<(), &str> {
+        let Ok(state) = self.state_port.recv().await else {
+            return Err("State Receive Error.");
+        };
+        match state.state {
+            ConnectionState::Connected => {
+                tracing::info!("[Connection] [id = {}] Connected", state.self_id)
+            }
+            ConnectionState::Disconnected => tracing::warn!(
+                "[Connection] [id = {}] Disconnected: {}",
+                state.self_id,
+                state.reason
+            ),
+        }
+        let _ = self.hubs.connection_hub.send(state);
+        Ok(())
+    }
+
     async fn handle_event(&self) -> Result<(), &str> {
         let Ok(res_value) = self.ws_port.recv().await else {
             return Err("Event Receive Error.");
         };
+        let _ = self.hubs.raw_event_hub.send(Arc::new(res_value.clone()));
         let any_event_res = serde_json::from_value::<AnyEvent>(res_value.clone());
         let any_event = match any_event_res {
             Ok(message) => message,
@@ -34,8 +63,21 @@ impl EventManager {
                 return Err("Serde Error");
             }
         };
+        // 自身消息只进入 message_sent 端口, 不进入全量与普通消息广播, 避免插件被自己的消息再次触发
+        let any_event = match any_event {
+            AnyEvent::MessageSent(msg_event) => {
+                self.handle_self_message(msg_event);
+                return Ok(());
+            }
+            AnyEvent::Message(msg_event) if msg_event.is_from_self() => {
+                self.handle_self_message(msg_event);
+                return Ok(());
+            }
+            any_event => any_event,
+        };
         let _ = self.hubs.all_event_hub.send(any_event.clone());
         match any_event {
+            AnyEvent::MessageSent(_) => {}
             AnyEvent::Message(msg_event) => match msg_event {
                 MessageEvent::Group(group_msg) => {
                     let _ = self.hubs.group_message_hub.send(group_msg.clone());
@@ -43,7 +85,7 @@ impl EventManager {
                         "[Group: {}-{}] [{}-{}]: {}",
                         group_msg.group_name,
                         group_msg.group_id,
-                        group_msg.sender.nickname,
+                        group_msg.display_name(),
                         group_msg.sender.user_id,
                         group_msg.raw_message
                     );
@@ -61,10 +103,17 @@ impl EventManager {
             AnyEvent::Meta(meta_event) => match meta_event {
                 MetaEvent::LifeCycle(life_cycle) => {
                     let _ = self.hubs.lifecycle_hub.send(life_cycle.clone());
-                    tracing::info!(
-                        "[LifeCycle] [id = {}] Napcat Already Connected",
-                        life_cycle.self_id
-                    );
+                    if life_cycle.sub_type == "disable" {
+                        tracing::warn!(
+                            "[LifeCycle] [id = {}] Napcat Disconnected",
+                            life_cycle.self_id
+                        );
+                    } else {
+                        tracing::info!(
+                            "[LifeCycle] [id = {}] Napcat Already Connected",
+                            life_cycle.self_id
+                        );
+                    }
                 }
                 MetaEvent::HeartBeat(heart_beat) => {
                     let _ = self.hubs.heartbeat_hub.send(heart_beat.clone());
@@ -72,14 +121,38 @@ impl EventManager {
                 }
             },
             AnyEvent::Notice(notice_event) => {
-                if !notice_event.status_text.is_empty() {
-                    tracing::info!(
-                        "[Notice] [self_id = {}] [type = {}] [user_id = {}] {}",
-                        notice_event.self_id,
-                        notice_event.notice_type,
-                        notice_event.user_id,
-                        notice_event.status_text
-                    );
+                tracing::info!(
+                    "[Notice] [self_id = {:?}] [type = {}] [group_id = {:?}]",
+                    notice_event.self_id(),
+                    res_value["notice_type"].as_str().unwrap_or(""),
+                    notice_event.group_id()
+                );
+                self.hubs.notice_hubs.dispatch(notice_event);
+            }
+            AnyEvent::Request(request_event) => {
+                let _ = self.hubs.request_hub.send(request_event.clone());
+                match request_event {
+                    RequestEvent::Friend(request) => {
+                        tracing::info!(
+                            "[Request] [好友] [{}]: {}",
+                            request.user_id,
+                            request.comment
+                        );
+                        let _ = self.hubs.friend_request_hub.send(request);
+                    }
+                    RequestEvent::Group(request) => {
+                        tracing::info!(
+                            "[Request] [群 {}] [{}] [{}]: {}",
+                            request.group_id,
+                            request.sub_type,
+                            request.user_id,
+                            request.comment
+                        );
+                        let _ = self.hubs.group_request_hub.send(request);
+                    }
+                    RequestEvent::Other => {
+                        tracing::info!("[Request] 未知的请求: {}", res_value);
+                    }
                 }
             }
             AnyEvent::Other => {
@@ -93,6 +166,22 @@ impl EventManager {
         Ok(())
     }
 
+    fn handle_self_message(&self, msg_event: MessageEvent) {
+        match &msg_event {
+            MessageEvent::Group(group_msg) => tracing::debug!(
+                "[Self] [Group: {}] {}",
+                group_msg.group_id,
+                group_msg.raw_message
+            ),
+            MessageEvent::Private(private_msg) => tracing::debug!(
+                "[Self] [Private: {:?}] {}",
+                private_msg.target_id,
+                private_msg.raw_message
+            ),
+        }
+        let _ = self.hubs.message_sent_hub.send(msg_event);
+    }
+
     pub fn get_event_nexus(&self) -> Arc<EventNexus> {
         self.hubs.get_nexus()
     }
@@ -105,6 +194,13 @@ impl EventManager {
             }
         };
         tokio::spawn(fut);
+        let arc_self = self.clone();
+        let fut = async move {
+            loop {
+                let _ = arc_self.clone().handle_state().await;
+            }
+        };
+        tokio::spawn(fut);
     }
 }
 
@@ -114,6 +210,13 @@ pub struct EventHubs {
     group_message_hub: Arc<SignalHub<Arc<GroupMessageEvent>>>,
     heartbeat_hub: Arc<SignalHub<Arc<HeartBeatEvent>>>,
     lifecycle_hub: Arc<SignalHub<Arc<LifeCycleEvent>>>,
+    connection_hub: Arc<SignalHub<Arc<ConnectionEvent>>>,
+    raw_event_hub: Arc<SignalHub<Arc<Value>>>,
+    message_sent_hub: Arc<SignalHub<Arc<MessageEvent>>>,
+    notice_hubs: NoticeHubs,
+    request_hub: Arc<SignalHub<Arc<RequestEvent>>>,
+    friend_request_hub: Arc<SignalHub<Arc<FriendRequestEvent>>>,
+    group_request_hub: Arc<SignalHub<Arc<GroupRequestEvent>>>,
 }
 
 impl EventHubs {
@@ -124,17 +227,18 @@ impl EventHubs {
             group_message_hub: Arc::new(SignalHub::new()),
             heartbeat_hub: Arc::new(SignalHub::new()),
             lifecycle_hub: Arc::new(SignalHub::new()),
+            connection_hub: Arc::new(SignalHub::new()),
+            raw_event_hub: Arc::new(SignalHub::new()),
+            message_sent_hub: Arc::new(SignalHub::new()),
+            notice_hubs: NoticeHubs::new(),
+            request_hub: Arc::new(SignalHub::new()),
+            friend_request_hub: Arc::new(SignalHub::new()),
+            group_request_hub: Arc::new(SignalHub::new()),
         }
     }
 
     pub fn get_nexus(&self) -> Arc<EventNexus> {
-        Arc::new(EventNexus {
-            all_event_hub: self.all_event_hub.clone(),
-            private_message_hub: self.private_message_hub.clone(),
-            group_message_hub: self.group_message_hub.clone(),
-            heartbeat_hub: self.heartbeat_hub.clone(),
-            lifecycle_hub: self.lifecycle_hub.clone(),
-        })
+        Arc::new(EventNexus::from_hubs(self))
     }
 }
 
@@ -150,22 +254,55 @@ pub struct EventNexus {
     group_message_hub: Arc<SignalHub<Arc<GroupMessageEvent>>>,
     heartbeat_hub: Arc<SignalHub<Arc<HeartBeatEvent>>>,
     lifecycle_hub: Arc<SignalHub<Arc<LifeCycleEvent>>>,
+    connection_hub: Arc<SignalHub<Arc<ConnectionEvent>>>,
+    raw_event_hub: Arc<SignalHub<Arc<Value>>>,
+    message_sent_hub: Arc<SignalHub<Arc<MessageEvent>>>,
+    notice_hubs: NoticeHubs,
+    request_hub: Arc<SignalHub<Arc<RequestEvent>>>,
+    friend_request_hub: Arc<SignalHub<Arc<FriendRequestEvent>>>,
+    group_request_hub: Arc<SignalHub<Arc<GroupRequestEvent>>>,
 }
 
 impl EventNexus {
+    /// 由各个广播单独组装; 通知、请求与自身消息端口使用新建的广播, 不会收到事件.
+    /// 需要全部端口时使用 from_hubs
     pub fn new(
         all_event_hub: Arc<SignalHub<Arc<AnyEvent>>>,
         private_message_hub: Arc<SignalHub<Arc<PrivateMessageEvent>>>,
         group_message_hub: Arc<SignalHub<Arc<GroupMessageEvent>>>,
         heartbeat_hub: Arc<SignalHub<Arc<HeartBeatEvent>>>,
         lifecycle_hub: Arc<SignalHub<Arc<LifeCycleEvent>>>,
+        connection_hub: Arc<SignalHub<Arc<ConnectionEvent>>>,
+        raw_event_hub: Arc<SignalHub<Arc<Value>>>,
     ) -> Self {
+        let hubs = EventHubs::new();
         Self {
             all_event_hub,
             private_message_hub,
             group_message_hub,
             heartbeat_hub,
             lifecycle_hub,
+            connection_hub,
+            raw_event_hub,
+            ..Self::from_hubs(&hubs)
+        }
+    }
+
+    /// 订阅 hubs 中各个事件的广播
+    pub fn from_hubs(hubs: &EventHubs) -> Self {
+        Self {
+            all_event_hub: hubs.all_event_hub.clone(),
+            private_message_hub: hubs.private_message_hub.clone(),
+            group_message_hub: hubs.group_message_hub.clone(),
+            heartbeat_hub: hubs.heartbeat_hub.clone(),
+            lifecycle_hub: hubs.lifecycle_hub.clone(),
+            connection_hub: hubs.connection_hub.clone(),
+            raw_event_hub: hubs.raw_event_hub.clone(),
+            message_sent_hub: hubs.message_sent_hub.clone(),
+            notice_hubs: hubs.notice_hubs.clone(),
+            request_hub: hubs.request_hub.clone(),
+            friend_request_hub: hubs.friend_request_hub.clone(),
+            group_request_hub: hubs.group_request_hub.clone(),
         }
     }
 
@@ -185,9 +322,112 @@ impl EventNexus {
         self.lifecycle_hub.get_port()
     }
 
+    pub fn get_connection_port(&self) -> SignalPort<Arc<ConnectionEvent>> {
+        self.connection_hub.get_port()
+    }
+
+    /// 全部已解析的事件, 不含机器人自己发出的消息 (见 message_sent 端口)
     pub fn get_all_event_port(&self) -> SignalPort<Arc<AnyEvent>> {
         self.all_event_hub.get_port()
     }
+
+    /// 未经解析的原始上报, 包括框架尚未建模的事件
+    pub fn get_raw_event_port(&self) -> SignalPort<Arc<Value>> {
+        self.raw_event_hub.get_port()
+    }
+
+    /// 机器人自己发出的消息: message_sent 上报, 以及发送者为自身的消息上报.
+    /// 这些消息不会出现在全量、私聊与群消息端口中
+    pub fn get_message_sent_port(&self) -> SignalPort<Arc<MessageEvent>> {
+        self.message_sent_hub.get_port()
+    }
+
+    /// 全部请求事件
+    pub fn get_request_port(&self) -> SignalPort<Arc<RequestEvent>> {
+        self.request_hub.get_port()
+    }
+
+    pub fn get_friend_request_port(&self) -> SignalPort<Arc<FriendRequestEvent>> {
+        self.friend_request_hub.get_port()
+    }
+
+    pub fn get_group_request_port(&self) -> SignalPort<Arc<GroupRequestEvent>> {
+        self.group_request_hub.get_port()
+    }
+
+    /// 全部通知事件
+    pub fn get_notice_port(&self) -> SignalPort<Arc<NoticeEvent>> {
+        self.notice_hubs.notice_hub.get_port()
+    }
+
+    pub fn get_group_recall_port(&self) -> SignalPort<Arc<GroupRecallNotice>> {
+        self.notice_hubs.group_recall_hub.get_port()
+    }
+
+    pub fn get_friend_recall_port(&self) -> SignalPort<Arc<FriendRecallNotice>> {
+        self.notice_hubs.friend_recall_hub.get_port()
+    }
+
+    pub fn get_group_increase_port(&self) -> SignalPort<Arc<GroupIncreaseNotice>> {
+        self.notice_hubs.group_increase_hub.get_port()
+    }
+
+    pub fn get_group_decrease_port(&self) -> SignalPort<Arc<GroupDecreaseNotice>> {
+        self.notice_hubs.group_decrease_hub.get_port()
+    }
+
+    pub fn get_group_admin_port(&self) -> SignalPort<Arc<GroupAdminNotice>> {
+        self.notice_hubs.group_admin_hub.get_port()
+    }
+
+    pub fn get_group_ban_port(&self) -> SignalPort<Arc<GroupBanNotice>> {
+        self.notice_hubs.group_ban_hub.get_port()
+    }
+
+    pub fn get_group_upload_port(&self) -> SignalPort<Arc<GroupUploadNotice>> {
+        self.notice_hubs.group_upload_hub.get_port()
+    }
+
+    pub fn get_friend_add_port(&self) -> SignalPort<Arc<FriendAddNotice>> {
+        self.notice_hubs.friend_add_hub.get_port()
+    }
+
+    pub fn get_poke_port(&self) -> SignalPort<Arc<PokeNotify>> {
+        self.notice_hubs.poke_hub.get_port()
+    }
+
+    pub fn get_lucky_king_port(&self) -> SignalPort<Arc<LuckyKingNotify>> {
+        self.notice_hubs.lucky_king_hub.get_port()
+    }
+
+    pub fn get_honor_port(&self) -> SignalPort<Arc<HonorNotify>> {
+        self.notice_hubs.honor_hub.get_port()
+    }
+
+    pub fn get_title_port(&self) -> SignalPort<Arc<TitleNotify>> {
+        self.notice_hubs.title_hub.get_port()
+    }
+
+    pub fn get_input_status_port(&self) -> SignalPort<Arc<InputStatusNotify>> {
+        self.notice_hubs.input_status_hub.get_port()
+    }
+
+    /// 群名变更 (NapCat 特有)
+    pub fn get_group_name_port(&self) -> SignalPort<Arc<GroupNameNotify>> {
+        self.notice_hubs.group_name_hub.get_port()
+    }
+
+    pub fn get_group_card_port(&self) -> SignalPort<Arc<GroupCardNotice>> {
+        self.notice_hubs.group_card_hub.get_port()
+    }
+
+    pub fn get_essence_port(&self) -> SignalPort<Arc<EssenceNotice>> {
+        self.notice_hubs.essence_hub.get_port()
+    }
+
+    pub fn get_group_msg_emoji_like_port(&self) -> SignalPort<Arc<GroupMsgEmojiLikeNotice>> {
+        self.notice_hubs.group_msg_emoji_like_hub.get_port()
+    }
 }
 
 impl Clone for EventNexus {
@@ -198,18 +438,132 @@ impl Clone for EventNexus {
             group_message_hub: self.group_message_hub.clone(),
             heartbeat_hub: self.heartbeat_hub.clone(),
             lifecycle_hub: self.lifecycle_hub.clone(),
+            connection_hub: self.connection_hub.clone(),
+            raw_event_hub: self.raw_event_hub.clone(),
+            message_sent_hub: self.message_sent_hub.clone(),
+            notice_hubs: self.notice_hubs.clone(),
+            request_hub: self.request_hub.clone(),
+            friend_request_hub: self.friend_request_hub.clone(),
+            group_request_hub: self.group_request_hub.clone(),
         }
     }
 }
 
 impl Default for EventNexus {
     fn default() -> Self {
-        Self::new(
-            Arc::new(SignalHub::new()),
-            Arc::new(SignalHub::new()),
-            Arc::new(SignalHub::new()),
-            Arc::new(SignalHub::new()),
-            Arc::new(SignalHub::new()),
-        )
+        Self::from_hubs(&EventHubs::new())
+    }
+}
+
+/// 各类通知事件的广播
+#[derive(Clone)]
+pub struct NoticeHubs {
+    notice_hub: Arc<SignalHub<Arc<NoticeEvent>>>,
+    group_recall_hub: Arc<SignalHub<Arc<GroupRecallNotice>>>,
+    friend_recall_hub: Arc<SignalHub<Arc<FriendRecallNotice>>>,
+    group_increase_hub: Arc<SignalHub<Arc<GroupIncreaseNotice>>>,
+    group_decrease_hub: Arc<SignalHub<Arc<GroupDecreaseNotice>>>,
+    group_admin_hub: Arc<SignalHub<Arc<GroupAdminNotice>>>,
+    group_ban_hub: Arc<SignalHub<Arc<GroupBanNotice>>>,
+    group_upload_hub: Arc<SignalHub<Arc<GroupUploadNotice>>>,
+    friend_add_hub: Arc<SignalHub<Arc<FriendAddNotice>>>,
+    poke_hub: Arc<SignalHub<Arc<PokeNotify>>>,
+    lucky_king_hub: Arc<SignalHub<Arc<LuckyKingNotify>>>,
+    honor_hub: Arc<SignalHub<Arc<HonorNotify>>>,
+    title_hub: Arc<SignalHub<Arc<TitleNotify>>>,
+    input_status_hub: Arc<SignalHub<Arc<InputStatusNotify>>>,
+    group_name_hub: Arc<SignalHub<Arc<GroupNameNotify>>>,
+    group_card_hub: Arc<SignalHub<Arc<GroupCardNotice>>>,
+    essence_hub: Arc<SignalHub<Arc<EssenceNotice>>>,
+    group_msg_emoji_like_hub: Arc<SignalHub<Arc<GroupMsgEmojiLikeNotice>>>,
+}
+
+impl NoticeHubs {
+    pub fn new() -> Self {
+        Self {
+            notice_hub: Arc::new(SignalHub::new()),
+            group_recall_hub: Arc::new(SignalHub::new()),
+            friend_recall_hub: Arc::new(SignalHub::new()),
+            group_increase_hub: Arc::new(SignalHub::new()),
+            group_decrease_hub: Arc::new(SignalHub::new()),
+            group_admin_hub: Arc::new(SignalHub::new()),
+            group_ban_hub: Arc::new(SignalHub::new()),
+            group_upload_hub: Arc::new(SignalHub::new()),
+            friend_add_hub: Arc::new(SignalHub::new()),
+            poke_hub: Arc::new(SignalHub::new()),
+            lucky_king_hub: Arc::new(SignalHub::new()),
+            honor_hub: Arc::new(SignalHub::new()),
+            title_hub: Arc::new(SignalHub::new()),
+            input_status_hub: Arc::new(SignalHub::new()),
+            group_name_hub: Arc::new(SignalHub::new()),
+            group_card_hub: Arc::new(SignalHub::new()),
+            essence_hub: Arc::new(SignalHub::new()),
+            group_msg_emoji_like_hub: Arc::new(SignalHub::new()),
+        }
+    }
+
+    /// 先广播到全部通知, 再广播到对应类型
+    fn dispatch(&self, notice: NoticeEvent) {
+        let _ = self.notice_hub.send(notice.clone());
+        match notice {
+            NoticeEvent::GroupRecall(e) => {
+                let _ = self.group_recall_hub.send(e);
+            }
+            NoticeEvent::FriendRecall(e) => {
+                let _ = self.friend_recall_hub.send(e);
+            }
+            NoticeEvent::GroupIncrease(e) => {
+                let _ = self.group_increase_hub.send(e);
+            }
+            NoticeEvent::GroupDecrease(e) => {
+                let _ = self.group_decrease_hub.send(e);
+            }
+            NoticeEvent::GroupAdmin(e) => {
+                let _ = self.group_admin_hub.send(e);
+            }
+            NoticeEvent::GroupBan(e) => {
+                let _ = self.group_ban_hub.send(e);
+            }
+            NoticeEvent::GroupUpload(e) => {
+                let _ = self.group_upload_hub.send(e);
+            }
+            NoticeEvent::FriendAdd(e) => {
+                let _ = self.friend_add_hub.send(e);
+            }
+            NoticeEvent::Notify(NotifyEvent::Poke(e)) => {
+                let _ = self.poke_hub.send(e);
+            }
+            NoticeEvent::Notify(NotifyEvent::LuckyKing(e)) => {
+                let _ = self.lucky_king_hub.send(e);
+            }
+            NoticeEvent::Notify(NotifyEvent::Honor(e)) => {
+                let _ = self.honor_hub.send(e);
+            }
+            NoticeEvent::Notify(NotifyEvent::Title(e)) => {
+                let _ = self.title_hub.send(e);
+            }
+            NoticeEvent::Notify(NotifyEvent::InputStatus(e)) => {
+                let _ = self.input_status_hub.send(e);
+            }
+            NoticeEvent::Notify(NotifyEvent::GroupName(e)) => {
+                let _ = self.group_name_hub.send(e);
+            }
+            NoticeEvent::GroupCard(e) => {
+                let _ = self.group_card_hub.send(e);
+            }
+            NoticeEvent::Essence(e) => {
+                let _ = self.essence_hub.send(e);
+            }
+            NoticeEvent::GroupMsgEmojiLike(e) => {
+                let _ = self.group_msg_emoji_like_hub.send(e);
+            }
+            NoticeEvent::Notify(_) | NoticeEvent::Other => {}
+        }
+    }
+}
+
+impl Default for NoticeHubs {
+    fn default() -> Self {
+        Self::new()
     }
 }
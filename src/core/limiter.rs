@@ -0,0 +1,260 @@
+use crate::config::{Config, RateLimit};
+use dashmap::DashMap;
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// 消息的发送目标
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Conversation {
+    Private(i64),
+    Group(i64),
+}
+
+/// 令牌桶: 以 rate 个/秒的速度补充, 最多积攒 burst 个
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            last: Instant::now(),
+        }
+    }
+
+    /// 取走一个令牌; 令牌不足时返回需要等待的时间
+    fn try_take(&mut self) -> Result<(), Duration> {
+        if self.limit.rate <= 0.0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let refill = now.duration_since(self.last).as_secs_f64() * self.limit.rate;
+        self.tokens = (self.tokens + refill).min(self.limit.burst.max(1) as f64);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - self.tokens) / self.limit.rate,
+        ))
+    }
+
+    /// 令牌已补满, 与新建的令牌桶等价
+    fn is_full(&self, now: Instant) -> bool {
+        let refill = now.duration_since(self.last).as_secs_f64() * self.limit.rate;
+        self.limit.rate <= 0.0 || self.tokens + refill >= self.limit.burst.max(1) as f64
+    }
+}
+
+/// 某账号下一个会话的发送队列
+#[derive(Default)]
+struct SendQueue {
+    /// tokio 的 Mutex 按等待顺序唤醒, 保证同一会话内消息按调用顺序发出
+    lock: Arc<tokio::sync::Mutex<()>>,
+    depth: AtomicUsize,
+}
+
+type QueueKey = (Option<i64>, Conversation);
+
+/// 每发出这么多条消息清理一次空闲的队列与令牌桶
+const PRUNE_INTERVAL: usize = 256;
+
+/// 发送消息的限流器: 全局、每个好友、每个群各一个令牌桶 (按账号区分),
+/// 同一会话的消息排队依次发送, 可选按文本长度模拟打字延迟
+pub struct RateLimiter {
+    global: RateLimit,
+    user: RateLimit,
+    group: RateLimit,
+    typing_delay_per_char: Duration,
+    typing_delay_max: Duration,
+    global_buckets: DashMap<Option<i64>, Arc<Mutex<TokenBucket>>>,
+    buckets: DashMap<QueueKey, Arc<Mutex<TokenBucket>>>,
+    queues: DashMap<QueueKey, Arc<SendQueue>>,
+    acquired: AtomicUsize,
+}
+
+/// 持有期间占据会话队列的队首, 释放后下一条消息才会发出
+pub struct SendPermit {
+    queue: Arc<SendQueue>,
+    _guard: Option<tokio::sync::OwnedMutexGuard<()>>,
+}
+
+impl Drop for SendPermit {
+    fn drop(&mut self) {
+        self.queue.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl RateLimiter {
+    pub fn new(global: RateLimit, user: RateLimit, group: RateLimit) -> Self {
+        Self {
+            global,
+            user,
+            group,
+            typing_delay_per_char: Duration::ZERO,
+            typing_delay_max: Duration::ZERO,
+            global_buckets: DashMap::new(),
+            buckets: DashMap::new(),
+            queues: DashMap::new(),
+            acquired: AtomicUsize::new(0),
+        }
+    }
+
+    /// 不限速, 仅保证同一会话内按顺序发送
+    pub fn unlimited() -> Self {
+        Self::new(
+            RateLimit::UNLIMITED,
+            RateLimit::UNLIMITED,
+            RateLimit::UNLIMITED,
+        )
+    }
+
+    pub fn from_config() -> Self {
+        let config = Config::get_or_init();
+        Self::new(
+            *config.rate_limit_global(),
+            *config.rate_limit_user(),
+            *config.rate_limit_group(),
+        )
+        .with_typing_delay(
+            Duration::from_millis(*config.typing_delay_per_char()),
+            Duration::from_millis(*config.typing_delay_max()),
+        )
+    }
+
+    /// 发送前按文本长度等待 per_char * 字数, 最多 max
+    pub fn with_typing_delay(mut self, per_char: Duration, max: Duration) -> Self {
+        self.typing_delay_per_char = per_char;
+        self.typing_delay_max = max;
+        self
+    }
+
+    /// 排队等待发送: 依次等待会话队列、会话令牌桶、账号全局令牌桶与打字延迟
+    pub async fn acquire(
+        &self,
+        self_id: Option<i64>,
+        conversation: Conversation,
+        text_len: usize,
+    ) -> SendPermit {
+        if self.acquired.fetch_add(1, Ordering::SeqCst) % PRUNE_INTERVAL == PRUNE_INTERVAL - 1 {
+            self.prune();
+        }
+        let key = (self_id, conversation);
+        // 在持有分片锁时计数, prune 不会移除即将使用的队列
+        let (queue, depth) = {
+            let queue = self.queues.entry(key).or_default();
+            let depth = queue.depth.fetch_add(1, Ordering::SeqCst) + 1;
+            (queue.clone(), depth)
+        };
+        if depth > 1 {
+            tracing::debug!("[限流] {:?} 排队中, 队列深度 {}", conversation, depth);
+        }
+        // 先占位再等待, 等待中被取消时也能在 drop 时扣减队列深度
+        let mut permit = SendPermit {
+            queue: queue.clone(),
+            _guard: None,
+        };
+        permit._guard = Some(queue.lock.clone().lock_owned().await);
+
+        let limit = match conversation {
+            Conversation::Private(_) => self.user,
+            Conversation::Group(_) => self.group,
+        };
+        // 不限速时不创建令牌桶
+        if limit.rate > 0.0 {
+            let bucket = self
+                .buckets
+                .entry(key)
+                .or_insert_with(|| Arc::new(Mutex::new(TokenBucket::new(limit))))
+                .clone();
+            Self::take(&bucket).await;
+        }
+        if self.global.rate > 0.0 {
+            let global = self
+                .global_buckets
+                .entry(self_id)
+                .or_insert_with(|| Arc::new(Mutex::new(TokenBucket::new(self.global))))
+                .clone();
+            Self::take(&global).await;
+        }
+
+        let typing = (self.typing_delay_per_char * text_len as u32).min(self.typing_delay_max);
+        if !typing.is_zero() {
+            tokio::time::sleep(typing).await;
+        }
+        permit
+    }
+
+    async fn take(bucket: &Mutex<TokenBucket>) {
+        loop {
+            let wait = bucket.lock().unwrap().try_take();
+            match wait {
+                Ok(()) => return,
+                Err(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// 清理没有消息等待的会话队列, 以及已补满 (与新建等价) 的令牌桶
+    pub fn prune(&self) {
+        self.queues
+            .retain(|_, queue| queue.depth.load(Ordering::SeqCst) > 0);
+        let now = Instant::now();
+        self.buckets
+            .retain(|_, bucket| !bucket.lock().unwrap().is_full(now));
+        self.global_buckets
+            .retain(|_, bucket| !bucket.lock().unwrap().is_full(now));
+    }
+
+    /// 已记录的会话队列数与令牌桶数
+    pub fn tracked(&self) -> (usize, usize) {
+        (
+            self.queues.len(),
+            self.buckets.len() + self.global_buckets.len(),
+        )
+    }
+
+    /// 某会话中等待发送 (含正在发送) 的消息数
+    pub fn queue_depth(&self, self_id: Option<i64>, conversation: Conversation) -> usize {
+        self.queues
+            .get(&(self_id, conversation))
+            .map(|queue| queue.depth.load(Ordering::SeqCst))
+            .unwrap_or(0)
+    }
+
+    /// 所有会话中等待发送的消息总数
+    pub fn total_queue_depth(&self) -> usize {
+        self.queues
+            .iter()
+            .map(|queue| queue.depth.load(Ordering::SeqCst))
+            .sum()
+    }
+
+    /// 各个非空会话的队列深度
+    pub fn queue_metrics(&self) -> Vec<(Option<i64>, Conversation, usize)> {
+        self.queues
+            .iter()
+            .map(|entry| {
+                let (self_id, conversation) = *entry.key();
+                (self_id, conversation, entry.depth.load(Ordering::SeqCst))
+            })
+            .filter(|(_, _, depth)| *depth > 0)
+            .collect()
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::from_config()
+    }
+}
@@ -0,0 +1,104 @@
+use crate::types::action_type::{ActionError, NapcatRequestData};
+use serde_json::{Value, json};
+use std::sync::{Arc, RwLock};
+
+/// 中间件对请求的处理结果
+pub enum Flow {
+    /// 继续交给下一个中间件并最终发出, 请求可以已被改写
+    Continue(NapcatRequestData),
+    /// 不再发出, 直接以给定结果作为响应
+    Block(Result<Value, ActionError>),
+}
+
+impl Flow {
+    /// 拦截请求并伪造一个成功的响应
+    pub fn respond(data: Value) -> Self {
+        Self::Block(Ok(json!({
+            "status": "ok",
+            "retcode": 0,
+            "data": data,
+            "message": "",
+            "wording": "",
+        })))
+    }
+
+    /// 拦截请求并返回 ActionError::Blocked
+    pub fn reject(reason: impl Into<String>) -> Self {
+        Self::Block(Err(ActionError::Blocked(reason.into())))
+    }
+}
+
+/// 动作中间件: 在请求发出前检查、改写、延迟或拦截, 并在完成后观察响应
+#[async_trait::async_trait]
+pub trait ActionMiddleware: Send + Sync {
+    /// 发出前调用; 在此等待即可延迟发送
+    async fn before(&self, data: NapcatRequestData) -> Flow {
+        Flow::Continue(data)
+    }
+
+    /// 请求完成 (含被拦截、失败、超时) 后调用
+    async fn after(&self, _data: &NapcatRequestData, _res: &Result<Value, ActionError>) {}
+}
+
+/// 按注册顺序执行 before, 按相反顺序执行 after
+#[derive(Default)]
+pub struct MiddlewareChain {
+    middlewares: RwLock<Vec<Arc<dyn ActionMiddleware>>>,
+}
+
+impl MiddlewareChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, middleware: Arc<dyn ActionMiddleware>) {
+        self.middlewares.write().unwrap().push(middleware);
+    }
+
+    pub fn len(&self) -> usize {
+        self.middlewares.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn snapshot(&self) -> Vec<Arc<dyn ActionMiddleware>> {
+        self.middlewares.read().unwrap().clone()
+    }
+
+    /// 依次经过各中间件后, 用 send 发出请求
+    pub async fn run<F, Fut>(&self, data: NapcatRequestData, send: F) -> Result<Value, ActionError>
+    where
+        F: FnOnce(NapcatRequestData) -> Fut,
+        Fut: Future<Output = Result<Value, ActionError>>,
+    {
+        let middlewares = self.snapshot();
+        if middlewares.is_empty() {
+            return send(data).await;
+        }
+        let mut data = data;
+        // 拦截时只有已经执行过 before 的中间件会收到 after
+        let mut entered = 0;
+        let mut blocked = None;
+        for middleware in &middlewares {
+            entered += 1;
+            match middleware.before(data.clone()).await {
+                Flow::Continue(next) => data = next,
+                Flow::Block(res) => {
+                    tracing::info!("[Middleware] [{}] 请求被拦截", data.action());
+                    blocked = Some(res);
+                    break;
+                }
+            }
+        }
+        let res = match blocked {
+            Some(res) => res,
+            None => send(data.clone()).await,
+        };
+        for middleware in middlewares[..entered].iter().rev() {
+            middleware.after(&data, &res).await;
+        }
+        res
+    }
+}
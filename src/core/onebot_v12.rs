@@ -0,0 +1,600 @@
+use crate::{
+    config::Config,
+    types::{
+        adapter_type::Adapter,
+        event_type::connection_event::ConnectionEvent,
+        signal_type::{SignalHub, SignalPort},
+    },
+};
+use dashmap::DashMap;
+use serde_json::{Map, Value, json};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::sync::oneshot;
+use tokio::time;
+
+const PLATFORM: &str = "qq";
+const INTERNAL_ECHO_PREFIX: &str = "v12:";
+
+/// OneBot v12 适配器: 包装一个传输层适配器 (如 NapcatAdapter 连接到 v12 实现端),
+/// 将 v12 事件、动作与消息段翻译为框架使用的 v11 模型
+pub struct OneBotV12Adapter {
+    inner: Arc<dyn Adapter>,
+    inner_event_port: SignalPort<Value>,
+    inner_action_port: SignalPort<Value>,
+    event_hub: Arc<SignalHub<Value>>,
+    action_hub: Arc<SignalHub<Value>>,
+    /// echo -> v11 动作名与参数, 用于翻译响应
+    pending_actions: DashMap<String, (String, Value)>,
+    /// 适配器自身发起的请求 (上传文件)
+    internal_requests: DashMap<String, oneshot::Sender<Value>>,
+    internal_atomic: AtomicU64,
+    message_ids: MessageIdMap,
+    /// 会话 -> (序号, 该会话上一条消息转发完成的通知), 保证同一会话的消息按顺序转发
+    lanes: DashMap<String, (u64, oneshot::Receiver<()>)>,
+    lane_atomic: AtomicU64,
+}
+
+/// v11 模型中的数字 message_id -> v12 字符串 message_id, 超出容量时丢弃最早的
+struct MessageIdMap {
+    capacity: usize,
+    ids: Mutex<(HashMap<i64, String>, VecDeque<i64>)>,
+}
+
+impl MessageIdMap {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ids: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    fn insert(&self, number: i64, id: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut guard = self.ids.lock().unwrap();
+        let (ids, order) = &mut *guard;
+        if ids.insert(number, id).is_some() {
+            return;
+        }
+        if order.len() >= self.capacity
+            && let Some(oldest) = order.pop_front()
+        {
+            ids.remove(&oldest);
+        }
+        order.push_back(number);
+    }
+
+    fn get(&self, number: i64) -> Option<String> {
+        self.ids.lock().unwrap().0.get(&number).cloned()
+    }
+
+    fn len(&self) -> usize {
+        self.ids.lock().unwrap().0.len()
+    }
+}
+
+impl OneBotV12Adapter {
+    /// message_id 映射的容量同 sent_history
+    pub fn new(inner: Arc<dyn Adapter>) -> Arc<Self> {
+        Self::with_id_capacity(inner, *Config::get_or_init().sent_history())
+    }
+
+    /// 指定最多记住多少个 v12 message_id; 更早的 ID 回译时按数字原样转为字符串
+    pub fn with_id_capacity(inner: Arc<dyn Adapter>, capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            inner_event_port: inner.get_event_port(),
+            inner_action_port: inner.get_action_port(),
+            inner,
+            event_hub: Arc::new(SignalHub::new()),
+            action_hub: Arc::new(SignalHub::new()),
+            pending_actions: DashMap::new(),
+            internal_requests: DashMap::new(),
+            internal_atomic: AtomicU64::new(0),
+            message_ids: MessageIdMap::new(capacity),
+            lanes: DashMap::new(),
+            lane_atomic: AtomicU64::new(0),
+        })
+    }
+
+    /// 当前记住的 message_id 数量
+    pub fn tracked_message_ids(&self) -> usize {
+        self.message_ids.len()
+    }
+
+    async fn pump_events(&self) {
+        loop {
+            let Ok(value) = self.inner_event_port.recv().await else {
+                continue;
+            };
+            match self.event_to_v11(&value) {
+                Some(event) => {
+                    let _ = self.event_hub.send(event);
+                }
+                None => tracing::warn!("[OneBot v12] 无法翻译的事件: {}", value),
+            }
+        }
+    }
+
+    /// 不带消息的动作直接按顺序转发; 消息可能需要上传文件, 在独立任务中翻译,
+    /// 但同一会话的消息仍按收到的顺序转发, 上传不会阻塞其他会话
+    async fn pump_actions(self: Arc<Self>) {
+        loop {
+            let Some(value) = self.action_hub.recv().await else {
+                continue;
+            };
+            let echo = value["echo"].as_str().unwrap_or("").to_string();
+            let action = value["action"].as_str().unwrap_or("").to_string();
+            self.pending_actions
+                .insert(echo, (action, value["params"].clone()));
+            let Some(lane) = Self::lane_key(&value) else {
+                let request = self.action_to_v12(value).await;
+                let _ = self.inner_action_port.send(request);
+                continue;
+            };
+            let (done_tx, done_rx) = oneshot::channel();
+            let ticket = self.lane_atomic.fetch_add(1, Ordering::SeqCst);
+            let previous = self.lanes.insert(lane.clone(), (ticket, done_rx));
+            let adapter = self.clone();
+            tokio::spawn(async move {
+                let request = adapter.action_to_v12(value).await;
+                if let Some((_, previous)) = previous {
+                    // 上一条转发完成或其任务已结束
+                    let _ = previous.await;
+                }
+                let _ = adapter.inner_action_port.send(request);
+                let _ = done_tx.send(());
+                adapter
+                    .lanes
+                    .remove_if(&lane, |_, (last, _)| *last == ticket);
+            });
+        }
+    }
+
+    /// 带消息的动作所属的会话; 其余动作返回 None
+    fn lane_key(request: &Value) -> Option<String> {
+        let params = &request["params"];
+        params.get("message")?;
+        let target = match &params["group_id"] {
+            Value::Null => format!("private:{}", params["user_id"]),
+            group_id => format!("group:{}", group_id),
+        };
+        Some(format!("{}:{}", request["self_id"], target))
+    }
+
+    /// 正在排队转发消息的会话数
+    pub fn busy_lanes(&self) -> usize {
+        self.lanes.len()
+    }
+
+    async fn pump_responses(&self) {
+        loop {
+            let Ok(value) = self.inner_action_port.recv().await else {
+                continue;
+            };
+            let echo = value["echo"].as_str().unwrap_or("").to_string();
+            if let Some((_, tx)) = self.internal_requests.remove(&echo) {
+                let _ = tx.send(value);
+                continue;
+            }
+            let Some((_, (action, params))) = self.pending_actions.remove(&echo) else {
+                continue;
+            };
+            let _ = self
+                .action_hub
+                .send(self.response_to_v11(&action, &params, value));
+        }
+    }
+
+    /// 将 v12 字符串 message_id 映射为 v11 模型使用的数字
+    fn map_message_id(&self, id: &Value) -> Value {
+        let Some(id) = id.as_str() else {
+            return id.clone();
+        };
+        let number = id.parse::<i64>().unwrap_or_else(|_| {
+            let hash = id.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+            (hash >> 1) as i64
+        });
+        self.message_ids.insert(number, id.to_string());
+        json!(number)
+    }
+
+    fn unmap_message_id(&self, id: &Value) -> Value {
+        match id.as_i64() {
+            Some(number) => json!(
+                self.message_ids
+                    .get(number)
+                    .unwrap_or_else(|| number.to_string())
+            ),
+            None => id.clone(),
+        }
+    }
+
+    fn event_to_v11(&self, event: &Value) -> Option<Value> {
+        let self_id = event["self"]["user_id"]
+            .as_str()
+            .and_then(|id| id.parse::<i64>().ok());
+        let time = event["time"].as_f64().map(|time| time as i64).unwrap_or(0);
+        let detail_type = event["detail_type"].as_str().unwrap_or("");
+        let mut v11 = match event["type"].as_str()? {
+            "message" => {
+                let message = segments_to_v11(&event["message"]);
+                let user_id = parse_id(&event["user_id"]);
+                let mut v11 = json!({
+                    "post_type": "message",
+                    "message_type": detail_type,
+                    "sub_type": event["sub_type"],
+                    "message_id": self.map_message_id(&event["message_id"]),
+                    "user_id": user_id,
+                    "raw_message": event["alt_message"].as_str().unwrap_or(""),
+                    "message": message,
+                    "sender": {
+                        "user_id": user_id,
+                        "nickname": "",
+                        "card": "",
+                    },
+                });
+                if detail_type == "group" {
+                    v11["group_id"] = parse_id(&event["group_id"]);
+                    v11["group_name"] = json!("");
+                }
+                v11
+            }
+            "meta" => match detail_type {
+                "heartbeat" => json!({
+                    "post_type": "meta_event",
+                    "meta_event_type": "heartbeat",
+                    "interval": event["interval"],
+                    "status": { "good": true, "online": true },
+                }),
+                "connect" => json!({
+                    "post_type": "meta_event",
+                    "meta_event_type": "lifecycle",
+                    "sub_type": "connect",
+                }),
+                "status_update" => {
+                    let bots = event["status"]["bots"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default();
+                    let bot = bots.first().cloned().unwrap_or(Value::Null);
+                    json!({
+                        "post_type": "meta_event",
+                        "meta_event_type": "heartbeat",
+                        "interval": 0,
+                        "self_id": parse_id(&bot["self"]["user_id"]),
+                        "status": {
+                            "good": event["status"]["good"].as_bool().unwrap_or(false),
+                            "online": bot["online"].as_bool().unwrap_or(false),
+                        },
+                    })
+                }
+                _ => return None,
+            },
+            "notice" => {
+                let notice_type = match detail_type {
+                    "friend_increase" => "friend_add",
+                    "private_message_delete" => "friend_recall",
+                    "group_member_increase" => "group_increase",
+                    "group_member_decrease" => "group_decrease",
+                    "group_message_delete" => "group_recall",
+                    other => other,
+                };
+                let mut v11 = ids_to_v11(event.clone());
+                v11["post_type"] = json!("notice");
+                v11["notice_type"] = json!(notice_type);
+                if let Some(message_id) = event.get("message_id") {
+                    v11["message_id"] = self.map_message_id(message_id);
+                }
+                v11
+            }
+            "request" => {
+                let mut v11 = ids_to_v11(event.clone());
+                v11["post_type"] = json!("request");
+                v11["request_type"] = json!(detail_type);
+                v11
+            }
+            _ => return None,
+        };
+        if v11.get("self_id").is_none_or(|id| id.is_null()) {
+            v11["self_id"] = json!(self_id.unwrap_or(0));
+        }
+        v11["time"] = json!(time);
+        if let Some(object) = v11.as_object_mut() {
+            object.remove("type");
+            object.remove("detail_type");
+            object.remove("self");
+        }
+        Some(v11)
+    }
+
+    async fn action_to_v12(&self, request: Value) -> Value {
+        let action = request["action"].as_str().unwrap_or("");
+        let mut params = request["params"].clone();
+        let (action, detail_type) = match action {
+            "send_private_msg" => ("send_message", Some("private")),
+            "send_group_msg" => ("send_message", Some("group")),
+            "delete_msg" => ("delete_message", None),
+            "get_msg" => ("get_message", None),
+            "get_login_info" => ("get_self_info", None),
+            "get_stranger_info" => ("get_user_info", None),
+            "get_version_info" => ("get_version", None),
+            "set_group_leave" => ("leave_group", None),
+            other => (other, None),
+        };
+        if let Some(message_id) = params.get("message_id").cloned() {
+            params["message_id"] = self.unmap_message_id(&message_id);
+        }
+        let message = params.get("message").cloned();
+        let mut params = ids_to_v12(params);
+        if let Some(detail_type) = detail_type {
+            params["detail_type"] = json!(detail_type);
+        }
+        if let Some(message) = message {
+            params["message"] = self.segments_to_v12(&message).await;
+        }
+        if let Some(self_id) = request["self_id"].as_i64() {
+            params["self"] = json!({ "platform": PLATFORM, "user_id": self_id.to_string() });
+        }
+        let mut v12 = json!({
+            "action": action,
+            "params": params,
+            "echo": request["echo"],
+        });
+        if let Some(self_id) = request.get("self_id") {
+            v12["self_id"] = self_id.clone();
+        }
+        v12
+    }
+
+    fn response_to_v11(&self, action: &str, params: &Value, response: Value) -> Value {
+        let mut data = response["data"].clone();
+        if let Some(message_id) = data.get("message_id").cloned() {
+            data["message_id"] = self.map_message_id(&message_id);
+        }
+        let mut data = ids_to_v11(data);
+        match action {
+            "get_login_info" | "get_stranger_info" | "get_friend_list" => {
+                for_each_item(&mut data, |user| user_to_v11(user, None));
+            }
+            "get_group_member_info" | "get_group_member_list" => {
+                let group_id = &params["group_id"];
+                for_each_item(&mut data, |member| user_to_v11(member, Some(group_id)));
+            }
+            _ => {}
+        }
+        json!({
+            "status": response["status"],
+            "retcode": response["retcode"],
+            "data": data,
+            "message": response["message"],
+            "wording": response["message"],
+            "echo": response["echo"],
+        })
+    }
+
+    /// v11 消息段 -> v12 消息段; 非 file_id 的媒体先经 upload_file 上传
+    async fn segments_to_v12(&self, message: &Value) -> Value {
+        let Some(segments) = message.as_array() else {
+            return message.clone();
+        };
+        let mut v12 = Vec::with_capacity(segments.len());
+        for segment in segments {
+            let data = &segment["data"];
+            let converted = match segment["type"].as_str().unwrap_or("") {
+                "at" if data["qq"] == "all" => json!({ "type": "mention_all", "data": {} }),
+                "at" => json!({
+                    "type": "mention",
+                    "data": { "user_id": id_to_string(&data["qq"]) },
+                }),
+                "reply" => json!({
+                    "type": "reply",
+                    "data": { "message_id": self.unmap_message_id(&parse_id(&data["id"])) },
+                }),
+                kind @ ("image" | "record" | "video" | "file") => {
+                    let kind = if kind == "record" { "voice" } else { kind };
+                    let file = data["file"].as_str().unwrap_or("");
+                    let file_id = self.upload_file(file).await;
+                    json!({ "type": kind, "data": { "file_id": file_id } })
+                }
+                _ => segment.clone(),
+            };
+            v12.push(converted);
+        }
+        Value::Array(v12)
+    }
+
+    /// 上传 v11 风格的文件引用 (URL、file://、base64://), 返回 file_id; 其他字符串视为已有 file_id
+    async fn upload_file(&self, file: &str) -> String {
+        let name = file
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or("file")
+            .to_string();
+        let params = if file.starts_with("http://") || file.starts_with("https://") {
+            json!({ "type": "url", "name": name, "url": file })
+        } else if let Some(data) = file.strip_prefix("base64://") {
+            json!({ "type": "data", "name": "file", "data": data })
+        } else if let Some(path) = file.strip_prefix("file://") {
+            json!({ "type": "path", "name": name, "path": path })
+        } else {
+            return file.to_string();
+        };
+        let key = format!(
+            "{}{}",
+            INTERNAL_ECHO_PREFIX,
+            self.internal_atomic.fetch_add(1, Ordering::SeqCst)
+        );
+        let (tx, rx) = oneshot::channel();
+        self.internal_requests.insert(key.clone(), tx);
+        let _ = self.inner_action_port.send(json!({
+            "action": "upload_file",
+            "params": params,
+            "echo": key,
+        }));
+        match time::timeout(time::Duration::from_secs(60), rx).await {
+            Ok(Ok(response)) => response["data"]["file_id"]
+                .as_str()
+                .unwrap_or(file)
+                .to_string(),
+            _ => {
+                self.internal_requests.remove(&key);
+                tracing::warn!("[OneBot v12] 上传文件失败: {}", name);
+                file.to_string()
+            }
+        }
+    }
+}
+
+impl Adapter for OneBotV12Adapter {
+    fn run(self: Arc<Self>) {
+        self.inner.clone().run();
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.pump_events().await });
+        tokio::spawn(self.clone().pump_actions());
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.pump_responses().await });
+    }
+
+    fn get_event_port(&self) -> SignalPort<Value> {
+        self.event_hub.get_port()
+    }
+
+    fn get_action_port(&self) -> SignalPort<Value> {
+        self.action_hub.get_port()
+    }
+
+    fn get_state_port(&self) -> SignalPort<ConnectionEvent> {
+        self.inner.get_state_port()
+    }
+}
+
+/// v12 消息段 -> v11 消息段; v11 模型中没有对应的消息段 (如 location 与扩展消息段) 被丢弃,
+/// 以免整条消息无法解析
+fn segments_to_v11(message: &Value) -> Value {
+    let Some(segments) = message.as_array() else {
+        return json!([]);
+    };
+    let segments = segments
+        .iter()
+        .filter_map(|segment| {
+            let data = &segment["data"];
+            let converted = match segment["type"].as_str().unwrap_or("") {
+                "text" => json!({ "type": "text", "data": { "text": data["text"] } }),
+                "mention" => json!({
+                    "type": "at",
+                    "data": { "qq": id_to_string(&data["user_id"]) },
+                }),
+                "mention_all" => json!({ "type": "at", "data": { "qq": "all" } }),
+                "reply" => json!({
+                    "type": "reply",
+                    "data": { "id": id_to_string(&data["message_id"]) },
+                }),
+                kind @ ("image" | "voice" | "audio" | "video" | "file") => {
+                    let kind = match kind {
+                        "voice" | "audio" => "record",
+                        kind => kind,
+                    };
+                    json!({ "type": kind, "data": { "file": data["file_id"] } })
+                }
+                other => {
+                    tracing::debug!("[OneBot v12] 丢弃不支持的消息段: {}", other);
+                    return None;
+                }
+            };
+            Some(converted)
+        })
+        .collect();
+    Value::Array(segments)
+}
+
+/// 对列表响应的每一项或单个对象响应执行 f
+fn for_each_item(data: &mut Value, f: impl Fn(&mut Map<String, Value>)) {
+    match data {
+        Value::Array(items) => items
+            .iter_mut()
+            .filter_map(|item| item.as_object_mut())
+            .for_each(f),
+        Value::Object(object) => f(object),
+        _ => {}
+    }
+}
+
+/// v12 用户信息 -> v11 字段名; 群成员信息中 user_displayname 为群名片, 并补上 group_id
+fn user_to_v11(user: &mut Map<String, Value>, group_id: Option<&Value>) {
+    let rename = |user: &mut Map<String, Value>, from: &str, to: &str| {
+        if let Some(value) = user.remove(from) {
+            user.entry(to).or_insert(value);
+        }
+    };
+    rename(user, "user_name", "nickname");
+    rename(user, "user_remark", "remark");
+    if let Some(group_id) = group_id {
+        rename(user, "user_displayname", "card");
+        user.entry("group_id").or_insert(group_id.clone());
+    }
+}
+
+fn parse_id(id: &Value) -> Value {
+    match id.as_str().and_then(|id| id.parse::<i64>().ok()) {
+        Some(id) => json!(id),
+        None => id.clone(),
+    }
+}
+
+fn id_to_string(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+fn is_id_key(key: &str) -> bool {
+    key == "user_id" || key == "group_id" || key.ends_with("_user_id") || key == "operator_id"
+}
+
+/// 将对象中的字符串 ID 转为数字 (v12 -> v11)
+fn ids_to_v11(value: Value) -> Value {
+    map_ids(value, &|id| parse_id(&id))
+}
+
+/// 将对象中的数字 ID 转为字符串 (v11 -> v12)
+fn ids_to_v12(value: Value) -> Value {
+    map_ids(value, &|id| match id {
+        Value::Number(id) => json!(id.to_string()),
+        id => id,
+    })
+}
+
+fn map_ids(value: Value, convert: &dyn Fn(Value) -> Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = if is_id_key(&key) {
+                        convert(value)
+                    } else {
+                        map_ids(value, convert)
+                    };
+                    (key, value)
+                })
+                .collect::<Map<String, Value>>(),
+        ),
+        Value::Array(array) => Value::Array(
+            array
+                .into_iter()
+                .map(|value| map_ids(value, convert))
+                .collect(),
+        ),
+        value => value,
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     config::Config,
-    core::event::EventNexus,
+    core::{dispatchar::Dispatcher, event::EventNexus},
     plugins::{ai_chat::AiChatPlugin, get_help::HelpPlugin},
     prelude::ActionManager,
     types::plugin_type::PluginWrapper,
@@ -12,6 +12,7 @@ pub struct PluginManager {
     plugins: Arc<RwLock<Vec<Arc<PluginWrapper>>>>,
     act: Arc<ActionManager>,
     event_nexus: Arc<EventNexus>,
+    dispatcher: Arc<Dispatcher>,
 }
 
 impl PluginManager {
@@ -20,9 +21,14 @@ impl PluginManager {
             plugins: Arc::new(RwLock::new(Vec::new())),
             act,
             event_nexus,
+            dispatcher: Dispatcher::new(),
         })
     }
 
+    pub fn dispatcher(&self) -> Arc<Dispatcher> {
+        self.dispatcher.clone()
+    }
+
     async fn handle_plugin(self: Arc<Self>) {
         tracing::info!(
             "[插件加载] [数量: {}] 加载中...",
@@ -32,8 +38,14 @@ impl PluginManager {
         for plugin in plugins {
             let plugin = plugin.clone();
             plugin.clone().on_plugin_load().await;
+            for handler in plugin.handlers() {
+                self.dispatcher.register(handler);
+            }
             tokio::spawn(plugin.run(self.event_nexus.clone(), self.act.clone()));
         }
+        self.dispatcher
+            .clone()
+            .run(self.event_nexus.clone(), self.act.clone());
     }
 
     pub async fn add_plugin(self: Arc<Self>, plugin: PluginWrapper) {
@@ -0,0 +1,412 @@
+use crate::types::{
+    adapter_type::Adapter,
+    event_type::connection_event::ConnectionEvent,
+    signal_type::{SignalHub, SignalPort},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncWriteExt, BufWriter},
+    sync::{Notify, mpsc},
+};
+
+/// 录制文件中一行记录的类别
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordKind {
+    /// 协议端上报的事件
+    Event,
+    /// 框架发出的动作
+    Action,
+    /// 协议端对动作的 echo 响应
+    Response,
+    /// 连接状态变化
+    State,
+}
+
+/// 录制文件 (JSONL) 中的一行
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordEntry {
+    /// 毫秒时间戳
+    pub time: i64,
+    pub kind: RecordKind,
+    pub data: Value,
+}
+
+impl RecordEntry {
+    fn now(kind: RecordKind, data: Value) -> Self {
+        Self {
+            time: chrono::Utc::now().timestamp_millis(),
+            kind,
+            data,
+        }
+    }
+}
+
+/// 读取一个录制文件, 跳过空行与无法解析的行
+pub fn read_recording(path: impl AsRef<Path>) -> std::io::Result<Vec<RecordEntry>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => tracing::warn!("[Replay] 第 {} 行无法解析: {}", index + 1, e),
+        }
+    }
+    Ok(entries)
+}
+
+/// 录制文件的写入端, 可在多处共享; 写入在独立的任务中进行, 不阻塞调用方
+#[derive(Clone)]
+pub struct Recorder {
+    tx: mpsc::UnboundedSender<RecordEntry>,
+}
+
+impl Recorder {
+    pub fn record(&self, kind: RecordKind, data: &Value) {
+        let _ = self.tx.send(RecordEntry::now(kind, data.clone()));
+    }
+}
+
+/// 依次写入记录, 暂无后续记录时落盘
+async fn write_entries(path: PathBuf, file: File, mut rx: mpsc::UnboundedReceiver<RecordEntry>) {
+    let mut writer = BufWriter::new(tokio::fs::File::from_std(file));
+    while let Some(entry) = rx.recv().await {
+        let Ok(line) = serde_json::to_string(&entry) else {
+            continue;
+        };
+        let mut res = writer.write_all(format!("{}\n", line).as_bytes()).await;
+        if res.is_ok() && rx.is_empty() {
+            res = writer.flush().await;
+        }
+        if let Err(e) = res {
+            tracing::error!("[Record] 写入 {} 失败: {}", path.display(), e);
+        }
+    }
+    let _ = writer.flush().await;
+}
+
+/// 录制适配器: 包装一个适配器, 将经过的原始事件、动作、响应与连接状态逐行写入 JSONL 文件.
+/// 经由 HTTP 传输发送的动作不经过适配器, 需将 recorder() 交给 ActionManager::set_recorder 录制
+pub struct RecordingAdapter {
+    inner: Arc<dyn Adapter>,
+    inner_event_port: SignalPort<Value>,
+    inner_action_port: SignalPort<Value>,
+    inner_state_port: SignalPort<ConnectionEvent>,
+    event_hub: Arc<SignalHub<Value>>,
+    action_hub: Arc<SignalHub<Value>>,
+    state_hub: Arc<SignalHub<ConnectionEvent>>,
+    recorder: Recorder,
+    /// 写入任务在 run 时启动
+    writer: Mutex<Option<(File, mpsc::UnboundedReceiver<RecordEntry>)>>,
+    path: PathBuf,
+}
+
+impl RecordingAdapter {
+    /// 录制到指定文件 (覆盖已有内容)
+    pub fn new(inner: Arc<dyn Adapter>, path: impl AsRef<Path>) -> std::io::Result<Arc<Self>> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path)?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        Ok(Arc::new(Self {
+            inner_event_port: inner.get_event_port(),
+            inner_action_port: inner.get_action_port(),
+            inner_state_port: inner.get_state_port(),
+            inner,
+            event_hub: Arc::new(SignalHub::new()),
+            action_hub: Arc::new(SignalHub::new()),
+            state_hub: Arc::new(SignalHub::new()),
+            recorder: Recorder { tx },
+            writer: Mutex::new(Some((file, rx))),
+            path,
+        }))
+    }
+
+    /// 在目录下创建以启动时间命名的录制文件, 如 napcat-20250101-120000.jsonl
+    pub fn in_dir(inner: Arc<dyn Adapter>, dir: impl AsRef<Path>) -> std::io::Result<Arc<Self>> {
+        std::fs::create_dir_all(&dir)?;
+        let name = format!(
+            "napcat-{}.jsonl",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        Self::new(inner, dir.as_ref().join(name))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 写入同一录制文件的句柄
+    pub fn recorder(&self) -> Recorder {
+        self.recorder.clone()
+    }
+
+    fn record(&self, kind: RecordKind, data: &Value) {
+        self.recorder.record(kind, data);
+    }
+
+    async fn pump_events(&self) {
+        loop {
+            let Ok(value) = self.inner_event_port.recv().await else {
+                continue;
+            };
+            self.record(RecordKind::Event, &value);
+            let _ = self.event_hub.send(value);
+        }
+    }
+
+    async fn pump_actions(&self) {
+        loop {
+            let Some(value) = self.action_hub.recv().await else {
+                continue;
+            };
+            self.record(RecordKind::Action, &value);
+            let _ = self.inner_action_port.send(value);
+        }
+    }
+
+    async fn pump_responses(&self) {
+        loop {
+            let Ok(value) = self.inner_action_port.recv().await else {
+                continue;
+            };
+            self.record(RecordKind::Response, &value);
+            let _ = self.action_hub.send(value);
+        }
+    }
+
+    async fn pump_states(&self) {
+        loop {
+            let Ok(state) = self.inner_state_port.recv().await else {
+                continue;
+            };
+            if let Ok(value) = serde_json::to_value(&state) {
+                self.record(RecordKind::State, &value);
+            }
+            let _ = self.state_hub.send(state);
+        }
+    }
+}
+
+impl Adapter for RecordingAdapter {
+    fn run(self: Arc<Self>) {
+        tracing::info!("[Record] 录制原始流量到 {}", self.path.display());
+        if let Some((file, rx)) = self.writer.lock().unwrap().take() {
+            tokio::spawn(write_entries(self.path.clone(), file, rx));
+        }
+        self.inner.clone().run();
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.pump_events().await });
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.pump_actions().await });
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.pump_responses().await });
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.pump_states().await });
+    }
+
+    fn get_event_port(&self) -> SignalPort<Value> {
+        self.event_hub.get_port()
+    }
+
+    fn get_action_port(&self) -> SignalPort<Value> {
+        self.action_hub.get_port()
+    }
+
+    fn get_state_port(&self) -> SignalPort<ConnectionEvent> {
+        self.state_hub.get_port()
+    }
+}
+
+/// 回放适配器: 按录制顺序重新上报事件与连接状态,
+/// 并以录制中同名动作的响应 (按顺序) 回复框架发出的动作
+pub struct ReplayAdapter {
+    entries: Vec<RecordEntry>,
+    event_hub: Arc<SignalHub<Value>>,
+    action_hub: Arc<SignalHub<Value>>,
+    state_hub: Arc<SignalHub<ConnectionEvent>>,
+    /// 动作名 -> 录制的响应队列
+    responses: Mutex<HashMap<String, VecDeque<Value>>>,
+    /// 回放期间框架实际发出的动作
+    replayed: Mutex<Vec<Value>>,
+    start_delay: Duration,
+    min_gap: Duration,
+    max_gap: Duration,
+    finished: AtomicBool,
+    finished_notify: Notify,
+}
+
+impl ReplayAdapter {
+    pub fn new(entries: Vec<RecordEntry>) -> Self {
+        let mut actions = HashMap::new();
+        for entry in entries.iter().filter(|e| e.kind == RecordKind::Action) {
+            if let (Some(echo), Some(action)) =
+                (entry.data["echo"].as_str(), entry.data["action"].as_str())
+            {
+                actions.insert(echo.to_string(), action.to_string());
+            }
+        }
+        let mut responses: HashMap<String, VecDeque<Value>> = HashMap::new();
+        for entry in entries.iter().filter(|e| e.kind == RecordKind::Response) {
+            let action = entry.data["echo"]
+                .as_str()
+                .and_then(|echo| actions.get(echo));
+            if let Some(action) = action {
+                responses
+                    .entry(action.clone())
+                    .or_default()
+                    .push_back(entry.data.clone());
+            }
+        }
+        Self {
+            entries,
+            event_hub: Arc::new(SignalHub::new()),
+            action_hub: Arc::new(SignalHub::new()),
+            state_hub: Arc::new(SignalHub::new()),
+            responses: Mutex::new(responses),
+            replayed: Mutex::new(Vec::new()),
+            start_delay: Duration::from_secs(1),
+            min_gap: Duration::from_millis(50),
+            max_gap: Duration::from_secs(1),
+            finished: AtomicBool::new(false),
+            finished_notify: Notify::new(),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::new(read_recording(path)?))
+    }
+
+    /// 开始回放前的等待时间, 留给插件订阅事件 (默认 1 秒)
+    pub fn with_start_delay(mut self, delay: Duration) -> Self {
+        self.start_delay = delay;
+        self
+    }
+
+    /// 相邻两条记录之间的回放间隔: 沿用录制时的间隔, 但限制在 [min, max] 之内
+    pub fn with_gap(mut self, min: Duration, max: Duration) -> Self {
+        self.min_gap = min;
+        self.max_gap = max.max(min);
+        self
+    }
+
+    /// 录制中框架发出的动作
+    pub fn recorded_actions(&self) -> Vec<Value> {
+        self.entries
+            .iter()
+            .filter(|e| e.kind == RecordKind::Action)
+            .map(|e| e.data.clone())
+            .collect()
+    }
+
+    /// 本次回放中框架发出的动作, 可与 recorded_actions 对比
+    pub fn replayed_actions(&self) -> Vec<Value> {
+        self.replayed.lock().unwrap().clone()
+    }
+
+    /// 等待全部事件回放完毕
+    pub async fn finished(&self) {
+        loop {
+            let notified = self.finished_notify.notified();
+            if self.finished.load(Ordering::Acquire) {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    async fn feed(&self) {
+        tokio::time::sleep(self.start_delay).await;
+        let mut last_time = None;
+        for entry in &self.entries {
+            if !matches!(entry.kind, RecordKind::Event | RecordKind::State) {
+                continue;
+            }
+            if let Some(last_time) = last_time {
+                let gap = Duration::from_millis(entry.time.saturating_sub(last_time).max(0) as u64);
+                tokio::time::sleep(gap.clamp(self.min_gap, self.max_gap)).await;
+            }
+            last_time = Some(entry.time);
+            match entry.kind {
+                RecordKind::Event => {
+                    let _ = self.event_hub.send(entry.data.clone());
+                }
+                _ => match serde_json::from_value::<ConnectionEvent>(entry.data.clone()) {
+                    Ok(state) => {
+                        let _ = self.state_hub.send(state);
+                    }
+                    Err(e) => tracing::warn!("[Replay] 无法解析的连接状态: {}", e),
+                },
+            }
+        }
+        tracing::info!("[Replay] 回放结束");
+        self.finished.store(true, Ordering::Release);
+        self.finished_notify.notify_waiters();
+    }
+
+    async fn answer_actions(&self) {
+        loop {
+            let Some(value) = self.action_hub.recv().await else {
+                continue;
+            };
+            let action = value["action"].as_str().unwrap_or("").to_string();
+            let echo = value["echo"].clone();
+            self.replayed.lock().unwrap().push(value);
+            let recorded = self
+                .responses
+                .lock()
+                .unwrap()
+                .get_mut(&action)
+                .and_then(|queue| queue.pop_front());
+            let mut response = recorded.unwrap_or_else(|| {
+                tracing::warn!("[Replay] 录制中没有 {} 的响应, 以空数据回复", action);
+                json!({
+                    "status": "ok",
+                    "retcode": 0,
+                    "data": null,
+                    "message": "",
+                    "wording": "",
+                })
+            });
+            response["echo"] = echo;
+            let _ = self.action_hub.send(response);
+        }
+    }
+}
+
+impl Adapter for ReplayAdapter {
+    fn run(self: Arc<Self>) {
+        tracing::info!("[Replay] 回放 {} 条记录", self.entries.len());
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.answer_actions().await });
+        let arc_self = self.clone();
+        tokio::spawn(async move { arc_self.feed().await });
+    }
+
+    fn get_event_port(&self) -> SignalPort<Value> {
+        self.event_hub.get_port()
+    }
+
+    fn get_action_port(&self) -> SignalPort<Value> {
+        self.action_hub.get_port()
+    }
+
+    fn get_state_port(&self) -> SignalPort<ConnectionEvent> {
+        self.state_hub.get_port()
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    config::Config,
+    core::{action::ActionManager, limiter::Conversation},
+    types::{action_type::ActionError, message_type::Message},
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+/// 机器人发出的一条消息
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SentMessage {
+    pub message_id: i64,
+    /// 发送账号, None 表示未指定账号
+    pub self_id: Option<i64>,
+    pub target: Conversation,
+    /// 发送时间 (秒)
+    pub time: i64,
+}
+
+impl SentMessage {
+    /// 以发送这条消息的账号执行动作
+    fn sender(&self, act: &ActionManager) -> Arc<ActionManager> {
+        act.for_account_or_current(self.self_id)
+    }
+
+    /// 撤回这条消息
+    pub async fn recall(&self, act: &ActionManager) -> Result<(), ActionError> {
+        self.sender(act).delete_msg(self.message_id).await
+    }
+
+    /// 在同一会话中回复这条消息
+    pub async fn reply(
+        &self,
+        act: &ActionManager,
+        message: Message,
+    ) -> Result<SentMessage, ActionError> {
+        let message = Message::new()
+            .with_reply(self.message_id.to_string())
+            .with_message(message);
+        self.sender(act).send_message(self.target, message).await
+    }
+
+    /// "编辑"消息: 撤回后在同一会话重新发送
+    pub async fn edit_by_recall_and_resend(
+        &self,
+        act: &ActionManager,
+        message: Message,
+    ) -> Result<SentMessage, ActionError> {
+        self.recall(act).await?;
+        self.sender(act).send_message(self.target, message).await
+    }
+}
+
+/// 最近发出的消息, 超出容量时丢弃最早的
+pub struct SentStore {
+    capacity: usize,
+    messages: Mutex<VecDeque<SentMessage>>,
+}
+
+impl SentStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            messages: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn from_config() -> Self {
+        Self::new(*Config::get_or_init().sent_history())
+    }
+
+    pub fn push(&self, message: SentMessage) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut messages = self.messages.lock().unwrap();
+        if messages.len() >= self.capacity {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+    }
+
+    /// 移除已撤回的消息
+    pub fn remove(&self, message_id: i64) {
+        self.messages
+            .lock()
+            .unwrap()
+            .retain(|message| message.message_id != message_id);
+    }
+
+    /// 最近的 n 条消息, 新的在前; 可按账号与会话筛选, 未记录账号的消息总会列出
+    pub fn recent(
+        &self,
+        self_id: Option<i64>,
+        target: Option<Conversation>,
+        n: usize,
+    ) -> Vec<SentMessage> {
+        self.messages
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|message| {
+                self_id.is_none() || message.self_id.is_none() || message.self_id == self_id
+            })
+            .filter(|message| target.is_none_or(|target| message.target == target))
+            .take(n)
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for SentStore {
+    fn default() -> Self {
+        Self::from_config()
+    }
+}
+
+impl ActionManager {
+    /// 本账号最近发出的 n 条消息, 新的在前; 未指定账号时不区分账号.
+    /// 经由未指定账号的实例发出的消息不知道发送账号, 对任何账号都会列出
+    pub fn recent_sent(&self, target: Option<Conversation>, n: usize) -> Vec<SentMessage> {
+        self.sent_store().recent(self.self_id(), target, n)
+    }
+}
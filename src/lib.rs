@@ -1,6 +1,8 @@
 pub mod bot;
 pub mod config;
 pub mod core;
-mod plugins;
+pub mod plugins;
 pub mod prelude;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod types;
@@ -1,6 +1,28 @@
-use meril_cat::prelude::MerilBot;
+use meril_cat::{
+    config::Config,
+    plugins::{recall::RecallPlugin, request::RequestPlugin},
+    prelude::{MerilBot, PluginWrapper},
+};
 #[tokio::main]
 async fn main() {
-    let bot = MerilBot::new();
+    let bot = match MerilBot::try_new() {
+        Ok(bot) => bot,
+        Err(e) => {
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    // 管理员指令仅在设置了 MERIL_ROOT_ID 时启用
+    let root_id = *Config::get_or_init().root_id();
+    if root_id != 0 {
+        let recall_plugin = PluginWrapper::new(RecallPlugin::new(root_id))
+            .with_name("Recall")
+            .with_description("/recall last N");
+        bot.register_plugin(recall_plugin).await;
+        let request_plugin = PluginWrapper::new(RequestPlugin::new(root_id))
+            .with_name("Request")
+            .with_description("/approve <flag> | /reject <flag> [理由]");
+        bot.register_plugin(request_plugin).await;
+    }
     bot.run().await;
 }
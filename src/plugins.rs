@@ -1,2 +1,4 @@
 pub mod ai_chat;
 pub mod get_help;
+pub mod recall;
+pub mod request;
@@ -1,6 +1,10 @@
 use crate::{
-    core::event::EventNexus,
+    core::{
+        dispatchar::{Handler, Matcher},
+        event::EventNexus,
+    },
     prelude::{ActionManager, BasePlugin, Message, PrivateMessageEvent},
+    types::event_type::message_event::MessageEvent,
 };
 use async_trait::async_trait;
 use chrono::Utc;
@@ -37,7 +41,7 @@ impl AiChatPlugin {
             .to_path_buf();
 
         let session: DashMap<String, Arc<RwLock<ChatSession>>> =
-            Self::read_history(file_dir.clone()).unwrap_or(DashMap::new());
+            Self::read_history(file_dir.clone()).unwrap_or_default();
 
         let mood_patches = MoodPatChes {
             neutral: "
@@ -107,6 +111,16 @@ impl AiChatPlugin {
         }
     }
 
+    /// 改用兼容 DeepSeek 接口的其他服务地址
+    pub fn with_base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        self.client = deepseek::Client::builder()
+            .api_key(self.token.clone())
+            .base_url(base_url)
+            .build()
+            .unwrap();
+        self
+    }
+
     fn save_history<P: AsRef<std::path::Path>>(&self, file_dir: P) -> Result<(), String> {
         let file_dir = file_dir.as_ref();
         if !file_dir.exists() {
@@ -257,7 +271,7 @@ impl AiChatPlugin {
                 state.history.drain(0..2);
             }
         }
-        return response;
+        response
     }
 }
 
@@ -277,10 +291,11 @@ impl AiChatPlugin {
         *chat_count += 1;
         if *chat_count == 2 {
             *chat_count = 0;
-            self.change_mood(msg.sender.user_id.clone(), msg.raw_message.clone())
+            self.change_mood(msg.sender.user_id, msg.raw_message.clone())
                 .await
                 .unwrap_or_else(|_| tracing::warn!("[Ai Plugin] Change Mood Error"));
         }
+        let act = act.for_account(msg.self_id);
         for text in response.split(';') {
             let _ = act
                 .send_private_message(
@@ -294,41 +309,55 @@ impl AiChatPlugin {
 #[async_trait]
 impl BasePlugin for AiChatPlugin {
     async fn on_load(self: Arc<Self>) {}
-    async fn on_update(self: Arc<Self>, event_nexus: Arc<EventNexus>, act: Arc<ActionManager>) {
-        let private_port = event_nexus.get_private_message_port();
+    async fn on_update(self: Arc<Self>, event_nexus: Arc<EventNexus>, _act: Arc<ActionManager>) {
         let heartbeat_port = event_nexus.get_heartbeat_port();
-        tokio::select! {
-            Ok(private_message) = private_port.recv() => {
-                if !private_message.clone().raw_message.starts_with("/") && !self.token.is_empty() {
-                    let msg = private_message.clone();
-                    let action = act.clone();
-                    let sf = self.clone();
-                    tokio::spawn(async move {
-                    sf.on_private_message(msg, action).await;
-                    });
-                }
-                let mood_state: AiMoodState;
-                {
-                    mood_state = self.mood_state.lock().await.clone();
-                }
-                if private_message.raw_message.starts_with("/mood") {
-                    let _ = act
-                        .send_private_message(
-                            private_message.sender.user_id.clone(),
-                            Message::new().with_text(format!(
-                                "[Mood]\npleasure: {}\naeousul: {}\ndominance: {}",
-                                mood_state.pleasure, mood_state.arousal, mood_state.dominance
-                            )),
-                        )
-                        .await;
-                }
-            },
-            Ok(_) = heartbeat_port.recv() => {
-                self.save_history(self.data_dir.clone()).unwrap_or_else(|_| tracing::warn!("[Ai Plugin Error] Save History Error"));
-            }
+        if heartbeat_port.recv().await.is_ok() {
+            self.save_history(self.data_dir.clone())
+                .unwrap_or_else(|_| tracing::warn!("[Ai Plugin Error] Save History Error"));
         }
     }
     async fn on_unload(self: Arc<Self>) {}
+    fn handlers(self: Arc<Self>) -> Vec<Handler> {
+        let sf = self.clone();
+        let mood = Handler::new("mood", move |event, act| {
+            let sf = sf.clone();
+            async move {
+                let mood_state = sf.mood_state.lock().await.clone();
+                let _ = act
+                    .for_account(event.self_id())
+                    .send_private_message(
+                        event.sender().user_id,
+                        Message::new().with_text(format!(
+                            "[Mood]\npleasure: {}\naeousul: {}\ndominance: {}",
+                            mood_state.pleasure, mood_state.arousal, mood_state.dominance
+                        )),
+                    )
+                    .await;
+            }
+        })
+        .with_matcher(Matcher::Private)
+        .with_matcher(Matcher::command("/mood"))
+        .with_priority(10)
+        .with_block(true);
+        if self.token.is_empty() {
+            return vec![mood];
+        }
+        // 命令不进入闲聊
+        let free_chat = Handler::new("free_chat", move |event, act| {
+            let sf = self.clone();
+            async move {
+                if let MessageEvent::Private(msg) = event.as_ref() {
+                    sf.on_private_message(Arc::new(msg.clone()), act).await;
+                }
+            }
+        })
+        .with_matcher(Matcher::Private)
+        .with_matcher(Matcher::custom(|event| {
+            !event.raw_message().starts_with('/')
+        }))
+        .with_priority(100);
+        vec![mood, free_chat]
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
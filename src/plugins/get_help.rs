@@ -1,7 +1,7 @@
 use crate::core::action::ActionManager;
 use crate::core::event::EventNexus;
 use crate::types::event_type::message_event::PrivateMessageEvent;
-use crate::types::message_type::Message;
+use crate::types::message_type::ForwardMessage;
 use crate::types::plugin_type::{BasePlugin, PluginWrapper};
 use async_trait::async_trait;
 use std::sync::Arc;
@@ -17,13 +17,15 @@ impl HelpPlugin {
 
     async fn on_private_message(&self, msg: Arc<PrivateMessageEvent>, act: Arc<ActionManager>) {
         if msg.raw_message.starts_with("/help") {
-            let mut info = String::from("[PluginList]\n");
+            // 每个插件一个节点, 以合并转发发出, 避免刷屏
+            let mut forward =
+                ForwardMessage::new().with_text_node(msg.self_id, "MerilBot", "[PluginList]");
             for plugin in self.plugins.read().await.iter() {
-                info.push_str(&format!("{}\n\n", plugin.get_info_str()));
+                forward = forward.with_text_node(msg.self_id, "MerilBot", plugin.get_info_str());
             }
-            let info = info.trim().to_string();
             let _ = act
-                .send_private_message(msg.sender.user_id, Message::new().with_text(info))
+                .for_account(msg.self_id)
+                .send_private_forward_msg(msg.sender.user_id, forward)
                 .await;
         }
     }
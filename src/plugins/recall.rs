@@ -0,0 +1,85 @@
+use crate::{
+    core::{
+        dispatchar::{Handler, Matcher},
+        event::EventNexus,
+        limiter::Conversation,
+    },
+    prelude::{ActionManager, BasePlugin, Message},
+    types::event_type::message_event::MessageEvent,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// 管理员指令 "/recall last N": 撤回机器人在当前会话最近发出的 N 条消息 (默认 1 条)
+pub struct RecallPlugin {
+    admin_id: i64,
+}
+
+impl RecallPlugin {
+    pub fn new(admin_id: i64) -> Self {
+        Self { admin_id }
+    }
+
+    /// 解析 "/recall" 或 "/recall last N"
+    fn parse(raw_message: &str) -> Option<usize> {
+        let mut args = raw_message.split_whitespace();
+        if args.next()? != "/recall" {
+            return None;
+        }
+        match (args.next(), args.next()) {
+            (None, _) => Some(1),
+            (Some("last"), None) => Some(1),
+            (Some("last"), Some(n)) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    async fn on_command(&self, act: Arc<ActionManager>, event: &MessageEvent) {
+        let Some(n) = Self::parse(&event.plain_text()) else {
+            return;
+        };
+        let target = match event.group_id() {
+            Some(group_id) => Conversation::Group(group_id),
+            None => Conversation::Private(event.sender().user_id),
+        };
+        let act = act.for_account(event.self_id());
+        let mut recalled = 0;
+        for sent in act.recent_sent(Some(target), n) {
+            match sent.recall(&act).await {
+                Ok(()) => recalled += 1,
+                Err(e) => tracing::warn!("[Recall] 撤回 {} 失败: {}", sent.message_id, e),
+            }
+        }
+        tracing::info!("[Recall] {:?} 已撤回 {} 条消息", target, recalled);
+        if recalled == 0 {
+            let _ = act
+                .send_message(
+                    target,
+                    Message::new().with_text("[Recall] 没有可撤回的消息"),
+                )
+                .await;
+        }
+    }
+}
+
+#[async_trait]
+impl BasePlugin for RecallPlugin {
+    async fn on_load(self: Arc<Self>) {}
+    async fn on_update(self: Arc<Self>, _event_nexus: Arc<EventNexus>, _act: Arc<ActionManager>) {
+        // 指令由 handlers 处理, 这里无事可做
+        std::future::pending::<()>().await;
+    }
+    async fn on_unload(self: Arc<Self>) {}
+    fn handlers(self: Arc<Self>) -> Vec<Handler> {
+        let admin_id = self.admin_id;
+        let recall = Handler::new("recall", move |event, act| {
+            let sf = self.clone();
+            async move { sf.on_command(act, &event).await }
+        })
+        .with_matcher(Matcher::command("/recall"))
+        .with_matcher(Matcher::Users(vec![admin_id]))
+        .with_priority(10)
+        .with_block(true);
+        vec![recall]
+    }
+}
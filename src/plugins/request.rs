@@ -0,0 +1,177 @@
+use crate::{
+    core::{
+        dispatchar::{Handler, Matcher},
+        event::EventNexus,
+    },
+    prelude::{ActionManager, BasePlugin, Message},
+    types::event_type::{message_event::MessageEvent, request_event::RequestEvent},
+};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::{sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+/// 处理好友请求与加群邀请: 来自管理员的直接同意, 其余转发给管理员,
+/// 由管理员回复 "/approve <flag>" 或 "/reject <flag> [理由]" 决定
+pub struct RequestPlugin {
+    admin_id: i64,
+    /// flag -> (收到时间, 等待管理员处理的请求)
+    pending: DashMap<String, (Instant, RequestEvent)>,
+    /// 最多保留的待处理请求数, 超出时丢弃最早的
+    capacity: usize,
+    /// 待处理请求的有效期, 过期后无法再处理
+    ttl: Duration,
+}
+
+impl RequestPlugin {
+    pub fn new(admin_id: i64) -> Self {
+        Self {
+            admin_id,
+            pending: DashMap::new(),
+            capacity: 100,
+            ttl: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// 最多保留 capacity 个待处理请求 (默认 100), 每个保留 ttl (默认一天)
+    pub fn with_pending_limit(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.capacity = capacity;
+        self.ttl = ttl;
+        self
+    }
+
+    /// 记下等待管理员处理的请求, 先清理过期的, 超出容量时丢弃最早的
+    fn remember(&self, flag: String, request: RequestEvent) {
+        self.pending
+            .retain(|_, (received, _)| received.elapsed() < self.ttl);
+        while self.pending.len() >= self.capacity.max(1) {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|entry| entry.value().0)
+                .map(|entry| entry.key().clone());
+            let Some(oldest) = oldest else {
+                break;
+            };
+            tracing::info!("[Request] 待处理请求过多, 丢弃 {}", oldest);
+            self.pending.remove(&oldest);
+        }
+        self.pending.insert(flag, (Instant::now(), request));
+    }
+
+    async fn on_request(&self, request: RequestEvent, act: Arc<ActionManager>) {
+        let (self_id, user_id, flag, text) = match &request {
+            RequestEvent::Friend(e) => (
+                e.self_id,
+                e.user_id,
+                e.flag.clone(),
+                format!(
+                    "[Request] 好友请求\n用户: {}\n验证: {}",
+                    e.user_id, e.comment
+                ),
+            ),
+            RequestEvent::Group(e) => (
+                e.self_id,
+                e.user_id,
+                e.flag.clone(),
+                format!(
+                    "[Request] {}\n群: {}\n用户: {}\n验证: {}",
+                    if e.is_invite() {
+                        "入群邀请"
+                    } else {
+                        "加群请求"
+                    },
+                    e.group_id,
+                    e.user_id,
+                    e.comment
+                ),
+            ),
+            RequestEvent::Other => return,
+        };
+        if self.admin_id == 0 {
+            tracing::info!("[Request] 未设置管理员, 请求 {} 留待手动处理", flag);
+            return;
+        }
+        if user_id == self.admin_id {
+            let res = match &request {
+                RequestEvent::Friend(e) => e.approve(&act).await,
+                RequestEvent::Group(e) => e.approve(&act).await,
+                RequestEvent::Other => return,
+            };
+            if let Err(e) = res {
+                tracing::warn!("[Request] 自动同意 {} 失败: {}", flag, e);
+            }
+            return;
+        }
+        let text = format!("{}\n/approve {}\n/reject {} [理由]", text, flag, flag);
+        self.remember(flag, request);
+        let _ = act
+            .for_account(self_id)
+            .send_private_message(self.admin_id, Message::new().with_text(text))
+            .await;
+    }
+
+    /// 管理员回复的 "/approve <flag>" 或 "/reject <flag> [理由]"
+    async fn on_command(&self, event: &MessageEvent, act: Arc<ActionManager>, approve: bool) {
+        let text = event.plain_text();
+        let mut args = text.split_whitespace().skip(1);
+        let Some(flag) = args.next() else {
+            return;
+        };
+        let reason = args.collect::<Vec<_>>().join(" ");
+        let pending = self
+            .pending
+            .remove(flag)
+            .filter(|(_, (received, _))| received.elapsed() < self.ttl);
+        let reply = match pending {
+            None => format!("[Request] 没有待处理的请求 {}", flag),
+            Some((_, (_, request))) => {
+                let res = match (&request, approve) {
+                    (RequestEvent::Friend(e), true) => e.approve(&act).await,
+                    (RequestEvent::Friend(e), false) => e.reject(&act, &reason).await,
+                    (RequestEvent::Group(e), true) => e.approve(&act).await,
+                    (RequestEvent::Group(e), false) => e.reject(&act, &reason).await,
+                    (RequestEvent::Other, _) => return,
+                };
+                match res {
+                    Ok(()) if approve => format!("[Request] 已同意 {}", flag),
+                    Ok(()) => format!("[Request] 已拒绝 {}", flag),
+                    Err(e) => format!("[Request] 处理 {} 失败: {}", flag, e),
+                }
+            }
+        };
+        let _ = act
+            .for_account(event.self_id())
+            .send_private_message(event.sender().user_id, Message::new().with_text(reply))
+            .await;
+    }
+}
+
+#[async_trait]
+impl BasePlugin for RequestPlugin {
+    async fn on_load(self: Arc<Self>) {}
+    async fn on_update(self: Arc<Self>, event_nexus: Arc<EventNexus>, act: Arc<ActionManager>) {
+        let request_port = event_nexus.get_request_port();
+        if let Ok(request) = request_port.recv().await {
+            self.on_request((*request).clone(), act).await;
+        }
+    }
+    async fn on_unload(self: Arc<Self>) {}
+    fn handlers(self: Arc<Self>) -> Vec<Handler> {
+        [("approve", "/approve", true), ("reject", "/reject", false)]
+            .into_iter()
+            .map(|(name, command, approve)| {
+                let sf = self.clone();
+                Handler::new(name, move |event, act| {
+                    let sf = sf.clone();
+                    async move { sf.on_command(&event, act, approve).await }
+                })
+                .with_matcher(Matcher::Private)
+                .with_matcher(Matcher::command(command))
+                .with_matcher(Matcher::Users(vec![self.admin_id]))
+                .with_priority(10)
+                .with_block(true)
+            })
+            .collect()
+    }
+}
@@ -1,10 +1,16 @@
 pub use crate::{
     bot::MerilBot,
-    core::{action::ActionManager, plugin::PluginManager},
+    core::{
+        action::ActionManager,
+        adapter::NapcatAdapter,
+        middleware::{ActionMiddleware, Flow},
+        plugin::PluginManager,
+    },
     types::{
-        action_type::NapcatRequestData,
+        action_type::{ActionError, NapcatRequestData, QuickOperation},
+        adapter_type::Adapter,
         event_type::message_event::{GroupMessageEvent, PrivateMessageEvent},
-        message_type::Message,
+        message_type::{ForwardMessage, MediaSource, Message},
         plugin_type::{BasePlugin, PluginWrapper},
     },
 };
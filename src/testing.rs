@@ -0,0 +1,499 @@
+//! 插件测试工具: 内存中的 MockAdapter 与 TestHarness, 无需 Napcat 即可驱动插件
+
+use crate::{
+    core::{
+        action::ActionManager, adapter::NapcatAdapter, dispatchar::Dispatcher, event::EventManager,
+        event::EventNexus,
+    },
+    types::{
+        action_type::NapcatRequestData,
+        adapter_type::Adapter,
+        event_type::connection_event::ConnectionEvent,
+        event_type::message_event::MessageEvent,
+        plugin_type::{BasePlugin, PluginWrapper},
+        signal_type::{SignalHub, SignalPort},
+    },
+};
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        atomic::{AtomicI64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{
+    io::DuplexStream,
+    sync::{Mutex, Notify},
+};
+use tokio_tungstenite::{WebSocketStream, tungstenite::Message};
+
+pub const TEST_SELF_ID: i64 = 10000;
+
+/// 对某个动作的预设响应
+#[derive(Clone, Debug)]
+pub enum MockResponse {
+    /// status: ok, 携带 data
+    Ok(Value),
+    /// status: failed, 携带 retcode 与错误信息
+    Failed { retcode: i64, message: String },
+    /// 不回复, 使请求一直等待 (超时或断线)
+    NoReply,
+    /// 原样回复 (echo 会被自动补上)
+    Raw(Value),
+    /// 等待一段时间后再回复, 期间其他动作照常处理
+    Delayed(Duration, Box<MockResponse>),
+}
+
+/// 内存中的适配器: 注入事件、记录动作、按脚本回复
+pub struct MockAdapter {
+    event_hub: Arc<SignalHub<Value>>,
+    action_hub: Arc<SignalHub<Value>>,
+    state_hub: Arc<SignalHub<ConnectionEvent>>,
+    sent: Mutex<Vec<NapcatRequestData>>,
+    sent_notify: Notify,
+    responses: DashMap<String, VecDeque<MockResponse>>,
+    next_message_id: AtomicI64,
+    pushed: AtomicUsize,
+}
+
+impl MockAdapter {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            event_hub: Arc::new(SignalHub::new()),
+            action_hub: Arc::new(SignalHub::new()),
+            state_hub: Arc::new(SignalHub::new()),
+            sent: Mutex::new(Vec::new()),
+            sent_notify: Notify::new(),
+            responses: DashMap::new(),
+            next_message_id: AtomicI64::new(1),
+            pushed: AtomicUsize::new(0),
+        })
+    }
+
+    /// 注入一条原始 OneBot v11 事件
+    pub fn push_event(&self, event: Value) {
+        self.pushed.fetch_add(1, Ordering::SeqCst);
+        let _ = self.event_hub.send(event);
+    }
+
+    /// 已注入的事件数
+    pub fn pushed_count(&self) -> usize {
+        self.pushed.load(Ordering::SeqCst)
+    }
+
+    pub fn push_private_message(&self, user_id: i64, text: &str) {
+        self.push_event(private_message(user_id, text));
+    }
+
+    pub fn push_group_message(&self, group_id: i64, user_id: i64, text: &str) {
+        self.push_event(group_message(group_id, user_id, text));
+    }
+
+    pub fn push_heartbeat(&self) {
+        self.push_event(json!({
+            "post_type": "meta_event",
+            "meta_event_type": "heartbeat",
+            "interval": 30000,
+            "self_id": TEST_SELF_ID,
+            "status": { "good": true, "online": true },
+            "time": chrono::Utc::now().timestamp(),
+        }));
+    }
+
+    pub fn connect(&self, self_id: i64) {
+        let _ = self.state_hub.send(ConnectionEvent::connected(self_id));
+    }
+
+    pub fn disconnect(&self, self_id: i64, reason: &str) {
+        let _ = self
+            .state_hub
+            .send(ConnectionEvent::disconnected(self_id, reason));
+    }
+
+    /// 为动作追加一条预设响应, 按追加顺序依次使用; 用尽后回复 `Ok(null)`
+    pub fn respond(&self, action: &str, response: MockResponse) {
+        self.responses
+            .entry(action.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// 到目前为止发送过的全部动作
+    pub async fn sent_actions(&self) -> Vec<NapcatRequestData> {
+        self.sent.lock().await.clone()
+    }
+
+    pub async fn clear_sent(&self) {
+        self.sent.lock().await.clear();
+    }
+
+    /// 等待第 index 个 (从 0 开始) 名为 action 的动作被发送
+    pub async fn wait_for_action(
+        &self,
+        action: &str,
+        index: usize,
+        timeout: Duration,
+    ) -> Option<NapcatRequestData> {
+        let wait = async {
+            loop {
+                let notified = self.sent_notify.notified();
+                if let Some(data) = self
+                    .sent
+                    .lock()
+                    .await
+                    .iter()
+                    .filter(|data| data.action() == action)
+                    .nth(index)
+                {
+                    return data.clone();
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.ok()
+    }
+
+    /// 未预设时的响应: 发送类动作返回递增的 message_id, 其余返回 null
+    fn default_response(&self, action: &str) -> MockResponse {
+        if action.starts_with("send_") && action.ends_with("_msg") {
+            let message_id = self.next_message_id.fetch_add(1, Ordering::SeqCst);
+            return MockResponse::Ok(json!({ "message_id": message_id }));
+        }
+        MockResponse::Ok(Value::Null)
+    }
+
+    async fn handle_actions(&self) {
+        loop {
+            let Some(value) = self.action_hub.recv().await else {
+                continue;
+            };
+            let Ok(data) = serde_json::from_value::<NapcatRequestData>(value.clone()) else {
+                tracing::warn!("[Mock] 无法解析的动作: {}", value);
+                continue;
+            };
+            let response = self
+                .responses
+                .get_mut(data.action())
+                .and_then(|mut queue| queue.pop_front())
+                .unwrap_or_else(|| self.default_response(data.action()));
+            self.sent.lock().await.push(data.clone());
+            self.sent_notify.notify_waiters();
+            let echo = data.echo().to_string();
+            if let MockResponse::Delayed(delay, response) = response {
+                let action_hub = self.action_hub.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    if let Some(reply) = Self::reply(*response, &echo) {
+                        let _ = action_hub.send(reply);
+                    }
+                });
+                continue;
+            }
+            if let Some(reply) = Self::reply(response, &echo) {
+                let _ = self.action_hub.send(reply);
+            }
+        }
+    }
+
+    fn reply(response: MockResponse, echo: &str) -> Option<Value> {
+        let reply = match response {
+            MockResponse::Ok(data) => json!({
+                "status": "ok",
+                "retcode": 0,
+                "data": data,
+                "message": "",
+                "wording": "",
+                "echo": echo,
+            }),
+            MockResponse::Failed { retcode, message } => json!({
+                "status": "failed",
+                "retcode": retcode,
+                "data": null,
+                "message": message,
+                "wording": message,
+                "echo": echo,
+            }),
+            MockResponse::NoReply => return None,
+            MockResponse::Raw(mut raw) => {
+                raw["echo"] = json!(echo);
+                raw
+            }
+            // 嵌套的延迟只计最外层
+            MockResponse::Delayed(_, response) => return Self::reply(*response, echo),
+        };
+        Some(reply)
+    }
+}
+
+impl Adapter for MockAdapter {
+    fn run(self: Arc<Self>) {
+        tokio::spawn(async move { self.handle_actions().await });
+    }
+
+    fn get_event_port(&self) -> SignalPort<Value> {
+        self.event_hub.get_port()
+    }
+
+    fn get_action_port(&self) -> SignalPort<Value> {
+        self.action_hub.get_port()
+    }
+
+    fn get_state_port(&self) -> SignalPort<ConnectionEvent> {
+        self.state_hub.get_port()
+    }
+}
+
+/// 由 MockAdapter 驱动的完整事件/动作管线, 可加载单个插件进行测试
+pub struct TestHarness {
+    pub adapter: Arc<MockAdapter>,
+    pub event: Arc<EventManager>,
+    pub action: Arc<ActionManager>,
+    pub event_nexus: Arc<EventNexus>,
+    pub dispatcher: Arc<Dispatcher>,
+    progress: Arc<Progress>,
+}
+
+/// 管线各阶段已处理的数量, 用于判断是否处理完已注入的事件
+#[derive(Default)]
+struct Progress {
+    /// EventManager 已接收的事件
+    events: AtomicUsize,
+    /// 已广播的私聊与群消息
+    messages: AtomicUsize,
+    /// 分发完成的消息
+    dispatched: AtomicUsize,
+}
+
+impl TestHarness {
+    pub fn new() -> Self {
+        let adapter = MockAdapter::new();
+        let event = EventManager::new(adapter.get_event_port(), adapter.get_state_port());
+        let action = ActionManager::new(adapter.get_action_port(), adapter.get_state_port());
+        let event_nexus = event.get_event_nexus();
+        let dispatcher = Dispatcher::new();
+        let progress = Arc::new(Progress::default());
+        // 先订阅再启动, 不漏掉任何事件
+        Self::track(&event_nexus, dispatcher.clone(), action.clone(), &progress);
+        adapter.clone().run();
+        event.clone().run();
+        action.clone().run();
+        action.cache().clone().run(event_nexus.clone());
+        Self {
+            adapter,
+            event,
+            action,
+            event_nexus,
+            dispatcher,
+            progress,
+        }
+    }
+
+    /// 代替 Dispatcher::run 分发消息, 同时记录各阶段的进度
+    fn track(
+        event_nexus: &Arc<EventNexus>,
+        dispatcher: Arc<Dispatcher>,
+        act: Arc<ActionManager>,
+        progress: &Arc<Progress>,
+    ) {
+        let raw_port = event_nexus.get_raw_event_port();
+        let private_port = event_nexus.get_private_message_port();
+        let group_port = event_nexus.get_group_message_port();
+        let events = progress.clone();
+        tokio::spawn(async move {
+            while raw_port.recv().await.is_ok() {
+                events.events.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let progress = progress.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    Ok(msg) = private_port.recv() => MessageEvent::Private((*msg).clone()),
+                    Ok(msg) = group_port.recv() => MessageEvent::Group((*msg).clone()),
+                    else => break,
+                };
+                progress.messages.fetch_add(1, Ordering::SeqCst);
+                let dispatcher = dispatcher.clone();
+                let act = act.clone();
+                let progress = progress.clone();
+                tokio::spawn(async move {
+                    dispatcher.dispatch(Arc::new(event), act).await;
+                    progress.dispatched.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+        });
+    }
+
+    /// 启动插件, 并等待其开始监听事件
+    pub async fn load_plugin<T>(&self, plugin: T)
+    where
+        T: BasePlugin + 'static,
+    {
+        self.load_wrapper(PluginWrapper::new(plugin)).await;
+    }
+
+    /// 启动已配置好 (名称、演练模式等) 的插件
+    pub async fn load_wrapper(&self, plugin: PluginWrapper) {
+        let plugin = Arc::new(plugin);
+        for handler in plugin.handlers() {
+            self.dispatcher.register(handler);
+        }
+        tokio::spawn(plugin.run(self.event_nexus.clone(), self.action.clone()));
+        self.settle().await;
+    }
+
+    /// 等待已注入的事件都被接收并分发完毕, 没有等待响应的请求, 且各阶段进度连续数轮不变;
+    /// 超过 5 秒仍未空闲则直接返回
+    pub async fn settle(&self) {
+        const QUIET_ROUNDS: usize = 8;
+        let wait = async {
+            let mut last = None;
+            let mut quiet = 0;
+            while quiet < QUIET_ROUNDS {
+                tokio::task::yield_now().await;
+                let progress = self.progress();
+                let (pushed, events, messages, dispatched, pending) = progress;
+                let idle = events >= pushed && dispatched >= messages && pending == 0;
+                quiet = if idle && last == Some(progress) {
+                    quiet + 1
+                } else {
+                    0
+                };
+                last = Some(progress);
+            }
+        };
+        if tokio::time::timeout(Duration::from_secs(5), wait)
+            .await
+            .is_err()
+        {
+            tracing::warn!("[Mock] 等待管线空闲超时");
+        }
+    }
+
+    /// (已注入, 已接收, 已广播的消息, 已分发, 等待响应) 的数量
+    fn progress(&self) -> (usize, usize, usize, usize, usize) {
+        (
+            self.adapter.pushed_count(),
+            self.progress.events.load(Ordering::SeqCst),
+            self.progress.messages.load(Ordering::SeqCst),
+            self.progress.dispatched.load(Ordering::SeqCst),
+            self.action.pending_count(),
+        )
+    }
+}
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 经内存中的 WebSocket 连入 NapcatAdapter 的模拟 Napcat, 用于测试连接与路由
+pub struct NapcatPeer {
+    stream: WebSocketStream<DuplexStream>,
+}
+
+impl NapcatPeer {
+    /// 以正向连接的方式接入适配器 (经由 NapcatAdapter::attach)
+    pub async fn connect(adapter: &Arc<NapcatAdapter>) -> Self {
+        let (client_io, server_io) = tokio::io::duplex(1 << 16);
+        let adapter = adapter.clone();
+        tokio::spawn(async move {
+            match tokio_tungstenite::client_async("ws://napcat/", client_io).await {
+                Ok((stream, _)) => adapter.attach(stream).await,
+                Err(e) => tracing::warn!("[Mock] 握手失败: {}", e),
+            }
+        });
+        let stream = tokio_tungstenite::accept_async(server_io)
+            .await
+            .expect("websocket handshake");
+        Self { stream }
+    }
+
+    pub async fn send(&mut self, value: Value) {
+        let _ = self
+            .stream
+            .send(Message::Text(value.to_string().into()))
+            .await;
+    }
+
+    /// 上报 lifecycle connect, 使连接绑定到 self_id
+    pub async fn hello(&mut self, self_id: i64) {
+        self.send(json!({
+            "post_type": "meta_event",
+            "meta_event_type": "lifecycle",
+            "sub_type": "connect",
+            "self_id": self_id,
+            "time": chrono::Utc::now().timestamp(),
+        }))
+        .await;
+    }
+
+    /// 等待适配器发来的下一个动作
+    pub async fn recv(&mut self, timeout: Duration) -> Option<Value> {
+        let wait = async {
+            while let Some(Ok(msg)) = self.stream.next().await {
+                if let Message::Text(text) = msg {
+                    return serde_json::from_str(&text).ok();
+                }
+            }
+            None
+        };
+        tokio::time::timeout(timeout, wait).await.ok().flatten()
+    }
+
+    /// 接收下一个动作并以 data 回复成功, 返回收到的动作
+    pub async fn answer(&mut self, data: Value, timeout: Duration) -> Option<Value> {
+        let action = self.recv(timeout).await?;
+        self.send(json!({
+            "status": "ok",
+            "retcode": 0,
+            "data": data,
+            "message": "",
+            "wording": "",
+            "echo": action["echo"],
+        }))
+        .await;
+        Some(action)
+    }
+
+    pub async fn close(mut self) {
+        let _ = self.stream.close(None).await;
+    }
+}
+
+pub fn private_message(user_id: i64, text: &str) -> Value {
+    json!({
+        "post_type": "message",
+        "message_type": "private",
+        "sub_type": "friend",
+        "message_id": chrono::Utc::now().timestamp_micros(),
+        "self_id": TEST_SELF_ID,
+        "user_id": user_id,
+        "time": chrono::Utc::now().timestamp(),
+        "raw_message": text,
+        "sender": { "user_id": user_id, "nickname": "tester", "card": "" },
+        "message": [{ "type": "text", "data": { "text": text } }],
+    })
+}
+
+pub fn group_message(group_id: i64, user_id: i64, text: &str) -> Value {
+    json!({
+        "post_type": "message",
+        "message_type": "group",
+        "sub_type": "normal",
+        "message_id": chrono::Utc::now().timestamp_micros(),
+        "self_id": TEST_SELF_ID,
+        "user_id": user_id,
+        "group_id": group_id,
+        "group_name": "test group",
+        "time": chrono::Utc::now().timestamp(),
+        "raw_message": text,
+        "sender": { "user_id": user_id, "nickname": "tester", "card": "" },
+        "message": [{ "type": "text", "data": { "text": text } }],
+    })
+}
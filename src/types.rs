@@ -1,4 +1,5 @@
 pub mod action_type;
+pub mod adapter_type;
 pub mod event_type;
 pub mod message_type;
 pub mod plugin_type;
@@ -1,11 +1,116 @@
+use crate::types::message_type::Message;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-#[derive(Serialize, Deserialize, Clone)]
+use std::{fmt, time::Duration};
 
+/// 动作调用失败的原因
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionError {
+    /// 与协议端的连接已断开, 请求无法完成
+    TransportClosed(String),
+    /// 传输层错误 (HTTP 请求失败、鉴权失败等)
+    Transport(String),
+    /// 在规定时间内没有收到响应
+    Timeout,
+    /// 请求无法序列化
+    Serialize(String),
+    /// 协议端返回 status: failed
+    Failed { retcode: i64, message: String },
+    /// 响应数据无法反序列化为期望的类型
+    Deserialize(String),
+    /// 请求被中间件拦截, 未发出
+    Blocked(String),
+}
+
+impl ActionError {
+    /// 协议端返回的错误码, 仅 Failed 时存在
+    pub fn retcode(&self) -> Option<i64> {
+        match self {
+            Self::Failed { retcode, .. } => Some(*retcode),
+            _ => None,
+        }
+    }
+
+    /// 从协议端的响应中提取失败信息, status 为 ok 时返回 None
+    pub fn from_response(res: &Value) -> Option<Self> {
+        if res["status"] == "ok" || (res["status"].is_null() && res["retcode"] == 0) {
+            return None;
+        }
+        let message = [&res["wording"], &res["message"], &res["msg"]]
+            .into_iter()
+            .filter_map(|text| text.as_str())
+            .find(|text| !text.is_empty())
+            .unwrap_or("")
+            .to_string();
+        Some(Self::Failed {
+            retcode: res["retcode"].as_i64().unwrap_or(-1),
+            message,
+        })
+    }
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TransportClosed(reason) => write!(f, "connection closed: {}", reason),
+            Self::Transport(reason) => write!(f, "transport error: {}", reason),
+            Self::Timeout => write!(f, "action timed out"),
+            Self::Serialize(reason) => write!(f, "failed to serialize request: {}", reason),
+            Self::Failed { retcode, message } => {
+                write!(f, "action failed (retcode {}): {}", retcode, message)
+            }
+            Self::Deserialize(reason) => write!(f, "failed to deserialize response: {}", reason),
+            Self::Blocked(reason) => write!(f, "blocked by middleware: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+/// 失败重试策略, 仅作用于幂等动作 (get_* / can_*), 且只重试超时与传输层错误
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 首次失败后最多重试的次数
+    pub max_retries: u32,
+    /// 第一次重试前的等待时间, 之后每次翻倍
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            backoff,
+        }
+    }
+
+    /// 重复执行不会产生副作用的动作
+    pub fn is_idempotent(action: &str) -> bool {
+        action.starts_with("get_") || action.starts_with("can_")
+    }
+
+    /// 该错误是否值得重试
+    pub fn should_retry(error: &ActionError) -> bool {
+        matches!(
+            error,
+            ActionError::Timeout | ActionError::TransportClosed(_) | ActionError::Transport(_)
+        )
+    }
+
+    /// 第 attempt 次重试 (从 0 开始) 前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(attempt))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NapcatRequestData {
     action: String,
     echo: String,
     params: Value,
+    /// 发送该动作的账号, 由适配器用于路由, 不会发给 Napcat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    self_id: Option<i64>,
 }
 
 impl NapcatRequestData {
@@ -14,9 +119,31 @@ impl NapcatRequestData {
             action: String::new(),
             echo: String::new(),
             params: Value::Null,
+            self_id: None,
         }
     }
 
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    pub fn echo(&self) -> &str {
+        &self.echo
+    }
+
+    /// 供中间件就地改写参数
+    pub fn params_mut(&mut self) -> &mut Value {
+        &mut self.params
+    }
+
+    pub fn params(&self) -> &Value {
+        &self.params
+    }
+
+    pub fn self_id(&self) -> Option<i64> {
+        self.self_id
+    }
+
     pub fn with_action(mut self, action: impl Into<String>) -> Self {
         self.action = action.into();
         self
@@ -31,6 +158,11 @@ impl NapcatRequestData {
         self.params = data.into();
         self
     }
+
+    pub fn with_self_id(mut self, self_id: i64) -> Self {
+        self.self_id = Some(self_id);
+        self
+    }
 }
 
 impl Default for NapcatRequestData {
@@ -38,3 +170,283 @@ impl Default for NapcatRequestData {
         Self::new()
     }
 }
+
+/// OneBot v11 快速操作, 可作为 HTTP 上报的响应体, 或经由 `.handle_quick_operation` 执行
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct QuickOperation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_escape: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at_sender: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kick: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ban: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ban_duration: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approve: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remark: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl QuickOperation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回复触发事件的消息
+    pub fn with_reply(mut self, message: Message) -> Self {
+        self.reply = Some(message);
+        self
+    }
+
+    /// 群聊回复时 @ 发送者
+    pub fn with_at_sender(mut self, at_sender: bool) -> Self {
+        self.at_sender = Some(at_sender);
+        self
+    }
+
+    /// 撤回触发事件的消息
+    pub fn with_delete(mut self) -> Self {
+        self.delete = Some(true);
+        self
+    }
+
+    /// 将发送者踢出群聊
+    pub fn with_kick(mut self) -> Self {
+        self.kick = Some(true);
+        self
+    }
+
+    /// 禁言发送者 (秒)
+    pub fn with_ban(mut self, duration: i64) -> Self {
+        self.ban = Some(true);
+        self.ban_duration = Some(duration);
+        self
+    }
+
+    /// 处理加好友/加群请求
+    pub fn with_approve(mut self, approve: bool) -> Self {
+        self.approve = Some(approve);
+        self
+    }
+
+    pub fn with_remark(mut self, remark: impl Into<String>) -> Self {
+        self.remark = Some(remark.into());
+        self
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+/// 动作响应中 data 字段的类型
+pub mod response {
+    use crate::types::{
+        event_type::message_event::SenderInfo,
+        message_type::{MessageSegment, string_or_number},
+    };
+    use serde::{Deserialize, Serialize};
+
+    /// send_*_msg 的响应
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct MessageId {
+        pub message_id: i64,
+    }
+
+    /// get_msg 以及消息历史中的单条消息
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct MessageDetail {
+        pub message_id: i64,
+        #[serde(default)]
+        pub real_id: i64,
+        #[serde(default)]
+        pub message_seq: Option<i64>,
+        pub time: i64,
+        pub message_type: String,
+        #[serde(default)]
+        pub group_id: Option<i64>,
+        pub sender: SenderInfo,
+        #[serde(default)]
+        pub raw_message: String,
+        pub message: Vec<MessageSegment>,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct LoginInfo {
+        pub user_id: i64,
+        pub nickname: String,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct FriendInfo {
+        pub user_id: i64,
+        pub nickname: String,
+        #[serde(default)]
+        pub remark: String,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupInfo {
+        pub group_id: i64,
+        pub group_name: String,
+        #[serde(default)]
+        pub member_count: i64,
+        #[serde(default)]
+        pub max_member_count: i64,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupMemberInfo {
+        pub group_id: i64,
+        pub user_id: i64,
+        pub nickname: String,
+        #[serde(default)]
+        pub card: String,
+        #[serde(default)]
+        pub sex: String,
+        #[serde(default)]
+        pub age: i64,
+        #[serde(default)]
+        pub area: String,
+        #[serde(default)]
+        pub join_time: i64,
+        #[serde(default)]
+        pub last_sent_time: i64,
+        #[serde(default, deserialize_with = "string_or_number")]
+        pub level: String,
+        /// owner / admin / member
+        #[serde(default)]
+        pub role: String,
+        #[serde(default)]
+        pub unfriendly: bool,
+        #[serde(default)]
+        pub title: String,
+        #[serde(default)]
+        pub title_expire_time: i64,
+        #[serde(default)]
+        pub card_changeable: bool,
+    }
+
+    /// get_image / get_record 的响应
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct FileInfo {
+        /// 本地文件路径
+        pub file: String,
+        #[serde(default)]
+        pub url: String,
+        #[serde(default)]
+        pub file_name: String,
+        #[serde(default, deserialize_with = "string_or_number")]
+        pub file_size: String,
+    }
+
+    /// can_send_image 等能力查询的响应
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct CanSend {
+        pub yes: bool,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct Status {
+        #[serde(default)]
+        pub online: bool,
+        pub good: bool,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct VersionInfo {
+        pub app_name: String,
+        pub app_version: String,
+        #[serde(default)]
+        pub protocol_version: String,
+    }
+
+    /// upload_*_file 的响应, 部分实现不返回数据
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct UploadedFile {
+        #[serde(default)]
+        pub file_id: Option<String>,
+    }
+
+    /// get_group_file_url 的响应
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct FileUrl {
+        pub url: String,
+    }
+
+    /// 群文件
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupFile {
+        #[serde(default)]
+        pub group_id: i64,
+        pub file_id: String,
+        pub file_name: String,
+        #[serde(default)]
+        pub busid: i64,
+        #[serde(default)]
+        pub file_size: i64,
+        #[serde(default)]
+        pub upload_time: i64,
+        #[serde(default)]
+        pub dead_time: i64,
+        #[serde(default)]
+        pub modify_time: i64,
+        #[serde(default)]
+        pub download_times: i64,
+        #[serde(default)]
+        pub uploader: i64,
+        #[serde(default)]
+        pub uploader_name: String,
+    }
+
+    /// 群文件夹
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupFolder {
+        #[serde(default)]
+        pub group_id: i64,
+        pub folder_id: String,
+        pub folder_name: String,
+        #[serde(default)]
+        pub create_time: i64,
+        #[serde(default)]
+        pub creator: i64,
+        #[serde(default)]
+        pub creator_name: String,
+        #[serde(default)]
+        pub total_file_count: i64,
+    }
+
+    /// get_group_root_files 的响应
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupFiles {
+        #[serde(default, deserialize_with = "null_as_default")]
+        pub files: Vec<GroupFile>,
+        #[serde(default, deserialize_with = "null_as_default")]
+        pub folders: Vec<GroupFolder>,
+    }
+
+    /// 部分实现以 null 表示空列表
+    fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: Default + Deserialize<'de>,
+    {
+        Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+    }
+
+    /// get_group_msg_history 的响应
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct MessageHistory {
+        pub messages: Vec<MessageDetail>,
+    }
+}
@@ -0,0 +1,15 @@
+use crate::types::{event_type::connection_event::ConnectionEvent, signal_type::SignalPort};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// 协议适配器: 负责与协议端通信, 以 OneBot v11 JSON 与框架交换事件与动作
+pub trait Adapter: Send + Sync {
+    /// 启动适配器 (建立连接、启动后台任务), 应立即返回
+    fn run(self: Arc<Self>);
+    /// 上报事件流: 从端口接收协议端推送的事件
+    fn get_event_port(&self) -> SignalPort<Value>;
+    /// 动作通道: 向端口发送带 echo 的动作, 并从端口接收对应的响应
+    fn get_action_port(&self) -> SignalPort<Value>;
+    /// 连接状态变化
+    fn get_state_port(&self) -> SignalPort<ConnectionEvent>;
+}
@@ -5,14 +5,31 @@ use serde::{Deserialize, Serialize};
 pub enum AnyEvent {
     #[serde(rename = "message")]
     Message(message_event::MessageEvent),
+    /// 机器人自己发出的消息 (NapCat 开启上报自身消息时)
+    #[serde(rename = "message_sent")]
+    MessageSent(message_event::MessageEvent),
     #[serde(rename = "meta_event")]
     Meta(meta_event::MetaEvent),
     #[serde(rename = "notice")]
     Notice(notice_event::NoticeEvent),
+    #[serde(rename = "request")]
+    Request(request_event::RequestEvent),
     #[serde(other)]
     Other,
 }
 
+impl From<message_event::PrivateMessageEvent> for AnyEvent {
+    fn from(value: message_event::PrivateMessageEvent) -> Self {
+        AnyEvent::Message(value.into())
+    }
+}
+
+impl From<message_event::GroupMessageEvent> for AnyEvent {
+    fn from(value: message_event::GroupMessageEvent) -> Self {
+        AnyEvent::Message(value.into())
+    }
+}
+
 pub mod meta_event {
     use serde::{Deserialize, Serialize};
 
@@ -50,14 +67,61 @@ pub mod meta_event {
 }
 
 pub mod message_event {
-    use crate::types::message_type::MessageSegment;
+    use crate::types::message_type::{MessageSegment, string_or_number};
     use serde::{Deserialize, Serialize};
 
     #[derive(Deserialize, Serialize, Clone, Debug)]
     pub struct SenderInfo {
         pub user_id: i64,
         pub nickname: String,
+        #[serde(default)]
         pub card: String,
+        /// owner / admin / member, 仅群消息
+        #[serde(default)]
+        pub role: String,
+        /// 专属头衔, 仅群消息
+        #[serde(default)]
+        pub title: String,
+        /// 群等级, 仅群消息
+        #[serde(default, deserialize_with = "string_or_number")]
+        pub level: String,
+        #[serde(default)]
+        pub age: i64,
+        /// male / female / unknown
+        #[serde(default)]
+        pub sex: String,
+        /// 临时会话所在的群
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub group_id: Option<i64>,
+    }
+
+    impl SenderInfo {
+        pub fn is_owner(&self) -> bool {
+            self.role == "owner"
+        }
+
+        /// 管理员或群主
+        pub fn is_admin(&self) -> bool {
+            self.role == "admin" || self.is_owner()
+        }
+
+        /// 群名片, 为空时为昵称
+        pub fn display_name(&self) -> &str {
+            if self.card.is_empty() {
+                &self.nickname
+            } else {
+                &self.card
+            }
+        }
+    }
+
+    /// 匿名消息的匿名信息
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct Anonymous {
+        pub id: i64,
+        pub name: String,
+        /// 禁言匿名用户时需要传回的标识
+        pub flag: String,
     }
 
     #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -68,6 +132,29 @@ pub mod message_event {
         pub raw_message: String,
         pub sender: SenderInfo,
         pub message: Vec<MessageSegment>,
+        /// 机器人自己发出的私聊消息中为接收者
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub target_id: Option<i64>,
+        /// friend / group (群临时会话) / other
+        #[serde(default)]
+        pub sub_type: String,
+        /// 临时会话的来源 (NapCat 特有)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub temp_source: Option<i64>,
+        #[serde(default)]
+        pub font: i64,
+        /// array / string
+        #[serde(default)]
+        pub message_format: String,
+        #[serde(default)]
+        pub real_id: i64,
+    }
+
+    impl PrivateMessageEvent {
+        /// 是否为群临时会话
+        pub fn is_temp(&self) -> bool {
+            self.sub_type == "group"
+        }
     }
 
     #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -76,10 +163,36 @@ pub mod message_event {
         pub message_id: i64,
         pub self_id: i64,
         pub time: i64,
+        #[serde(default)]
         pub group_name: String,
         pub raw_message: String,
         pub sender: SenderInfo,
         pub message: Vec<MessageSegment>,
+        /// normal / anonymous / notice
+        #[serde(default)]
+        pub sub_type: String,
+        #[serde(default)]
+        pub anonymous: Option<Anonymous>,
+        #[serde(default)]
+        pub font: i64,
+        #[serde(default)]
+        pub message_format: String,
+        #[serde(default)]
+        pub real_id: i64,
+    }
+
+    impl GroupMessageEvent {
+        pub fn is_anonymous(&self) -> bool {
+            self.anonymous.is_some()
+        }
+
+        /// 发送者的显示名: 匿名消息为匿名名称, 否则为群名片或昵称
+        pub fn display_name(&self) -> &str {
+            match &self.anonymous {
+                Some(anonymous) => &anonymous.name,
+                None => self.sender.display_name(),
+            }
+        }
     }
 
     #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -101,6 +214,70 @@ pub mod message_event {
         Private(PrivateMessageEvent),
     }
 
+    impl MessageEvent {
+        pub fn self_id(&self) -> i64 {
+            match self {
+                Self::Group(e) => e.self_id,
+                Self::Private(e) => e.self_id,
+            }
+        }
+
+        pub fn sender(&self) -> &SenderInfo {
+            match self {
+                Self::Group(e) => &e.sender,
+                Self::Private(e) => &e.sender,
+            }
+        }
+
+        /// 是否为机器人自己发出的消息
+        pub fn is_from_self(&self) -> bool {
+            self.sender().user_id == self.self_id()
+        }
+
+        pub fn raw_message(&self) -> &str {
+            match self {
+                Self::Group(e) => &e.raw_message,
+                Self::Private(e) => &e.raw_message,
+            }
+        }
+
+        pub fn segments(&self) -> &[MessageSegment] {
+            match self {
+                Self::Group(e) => &e.message,
+                Self::Private(e) => &e.message,
+            }
+        }
+
+        /// 群消息所在的群, 私聊为 None
+        pub fn group_id(&self) -> Option<i64> {
+            match self {
+                Self::Group(e) => Some(e.group_id),
+                Self::Private(_) => None,
+            }
+        }
+
+        /// 全部文本消息段拼接后去掉首尾空白, 不含 @、回复等消息段
+        pub fn plain_text(&self) -> String {
+            self.segments()
+                .iter()
+                .filter_map(|segment| match segment {
+                    MessageSegment::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<String>()
+                .trim()
+                .to_string()
+        }
+
+        /// 是否 @ 了机器人
+        pub fn is_at_self(&self) -> bool {
+            let self_id = self.self_id().to_string();
+            self.segments()
+                .iter()
+                .any(|segment| matches!(segment, MessageSegment::At { qq } if *qq == self_id))
+        }
+    }
+
     impl From<PrivateMessageEvent> for MessageEvent {
         fn from(value: PrivateMessageEvent) -> Self {
             MessageEvent::Private(value)
@@ -141,15 +318,457 @@ pub mod message_event {
 }
 
 pub mod notice_event {
+    use crate::types::message_type::string_or_number;
     use serde::{Deserialize, Serialize};
 
     #[derive(Deserialize, Serialize, Clone, Debug)]
-    pub struct NoticeEvent {
-        pub group_id: u64,
-        pub notice_type: String,
-        pub self_id: u64,
+    #[serde(tag = "notice_type")]
+    pub enum NoticeEvent {
+        #[serde(rename = "group_recall")]
+        GroupRecall(GroupRecallNotice),
+        #[serde(rename = "friend_recall")]
+        FriendRecall(FriendRecallNotice),
+        #[serde(rename = "group_increase")]
+        GroupIncrease(GroupIncreaseNotice),
+        #[serde(rename = "group_decrease")]
+        GroupDecrease(GroupDecreaseNotice),
+        #[serde(rename = "group_admin")]
+        GroupAdmin(GroupAdminNotice),
+        #[serde(rename = "group_ban")]
+        GroupBan(GroupBanNotice),
+        #[serde(rename = "group_upload")]
+        GroupUpload(GroupUploadNotice),
+        #[serde(rename = "friend_add")]
+        FriendAdd(FriendAddNotice),
+        #[serde(rename = "notify")]
+        Notify(NotifyEvent),
+        #[serde(rename = "group_card")]
+        GroupCard(GroupCardNotice),
+        #[serde(rename = "essence")]
+        Essence(EssenceNotice),
+        #[serde(rename = "group_msg_emoji_like")]
+        GroupMsgEmojiLike(GroupMsgEmojiLikeNotice),
+        /// 尚未建模的通知, 可从原始上报中读取
+        #[serde(other)]
+        Other,
+    }
+
+    impl NoticeEvent {
+        pub fn self_id(&self) -> Option<i64> {
+            Some(match self {
+                Self::GroupRecall(e) => e.self_id,
+                Self::FriendRecall(e) => e.self_id,
+                Self::GroupIncrease(e) => e.self_id,
+                Self::GroupDecrease(e) => e.self_id,
+                Self::GroupAdmin(e) => e.self_id,
+                Self::GroupBan(e) => e.self_id,
+                Self::GroupUpload(e) => e.self_id,
+                Self::FriendAdd(e) => e.self_id,
+                Self::Notify(e) => e.self_id()?,
+                Self::GroupCard(e) => e.self_id,
+                Self::Essence(e) => e.self_id,
+                Self::GroupMsgEmojiLike(e) => e.self_id,
+                Self::Other => return None,
+            })
+        }
+
+        /// 通知所在的群, 好友相关的通知为 None
+        pub fn group_id(&self) -> Option<i64> {
+            match self {
+                Self::GroupRecall(e) => Some(e.group_id),
+                Self::GroupIncrease(e) => Some(e.group_id),
+                Self::GroupDecrease(e) => Some(e.group_id),
+                Self::GroupAdmin(e) => Some(e.group_id),
+                Self::GroupBan(e) => Some(e.group_id),
+                Self::GroupUpload(e) => Some(e.group_id),
+                Self::Notify(e) => e.group_id(),
+                Self::GroupCard(e) => Some(e.group_id),
+                Self::Essence(e) => Some(e.group_id),
+                Self::GroupMsgEmojiLike(e) => Some(e.group_id),
+                Self::FriendRecall(_) | Self::FriendAdd(_) | Self::Other => None,
+            }
+        }
+    }
+
+    /// 群消息撤回; operator_id 与 user_id 不同时为管理员撤回
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupRecallNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        pub user_id: i64,
+        #[serde(default)]
+        pub operator_id: i64,
+        pub message_id: i64,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct FriendRecallNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub user_id: i64,
+        pub message_id: i64,
+    }
+
+    /// 群成员增加, sub_type 为 approve 或 invite
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupIncreaseNotice {
+        pub self_id: i64,
+        pub time: i64,
+        #[serde(default)]
+        pub sub_type: String,
+        pub group_id: i64,
+        #[serde(default)]
+        pub operator_id: i64,
+        pub user_id: i64,
+    }
+
+    /// 群成员减少, sub_type 为 leave、kick、kick_me 或 disband
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupDecreaseNotice {
+        pub self_id: i64,
+        pub time: i64,
+        #[serde(default)]
+        pub sub_type: String,
+        pub group_id: i64,
+        #[serde(default)]
+        pub operator_id: i64,
+        pub user_id: i64,
+    }
+
+    /// 管理员变动, sub_type 为 set 或 unset
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupAdminNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub sub_type: String,
+        pub group_id: i64,
+        pub user_id: i64,
+    }
+
+    /// 群禁言, sub_type 为 ban 或 lift_ban; user_id 为 0 时为全员禁言
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupBanNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub sub_type: String,
+        pub group_id: i64,
+        #[serde(default)]
+        pub operator_id: i64,
+        pub user_id: i64,
+        /// 禁言时长 (秒)
+        #[serde(default)]
+        pub duration: i64,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupUploadNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        pub user_id: i64,
+        pub file: UploadedFileInfo,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct UploadedFileInfo {
+        pub id: String,
+        pub name: String,
+        #[serde(default)]
+        pub size: i64,
+        #[serde(default)]
+        pub busid: i64,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct FriendAddNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub user_id: i64,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupCardNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        pub user_id: i64,
+        #[serde(default)]
+        pub card_new: String,
+        #[serde(default)]
+        pub card_old: String,
+    }
+
+    /// 精华消息, sub_type 为 add 或 delete
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EssenceNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub sub_type: String,
+        pub group_id: i64,
+        pub message_id: i64,
+        #[serde(default)]
+        pub sender_id: i64,
+        #[serde(default)]
+        pub operator_id: i64,
+    }
+
+    /// 群消息表情回应
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupMsgEmojiLikeNotice {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        pub user_id: i64,
+        pub message_id: i64,
+        #[serde(default)]
+        pub likes: Vec<EmojiLike>,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct EmojiLike {
+        #[serde(deserialize_with = "string_or_number")]
+        pub emoji_id: String,
+        #[serde(default)]
+        pub count: i64,
+    }
+
+    /// notify 类通知, 以 sub_type 区分
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    #[serde(tag = "sub_type")]
+    pub enum NotifyEvent {
+        #[serde(rename = "poke")]
+        Poke(PokeNotify),
+        #[serde(rename = "lucky_king")]
+        LuckyKing(LuckyKingNotify),
+        #[serde(rename = "honor")]
+        Honor(HonorNotify),
+        #[serde(rename = "title")]
+        Title(TitleNotify),
+        #[serde(rename = "input_status")]
+        InputStatus(InputStatusNotify),
+        #[serde(rename = "group_name")]
+        GroupName(GroupNameNotify),
+        #[serde(other)]
+        Other,
+    }
+
+    impl NotifyEvent {
+        pub fn self_id(&self) -> Option<i64> {
+            Some(match self {
+                Self::Poke(e) => e.self_id,
+                Self::LuckyKing(e) => e.self_id,
+                Self::Honor(e) => e.self_id,
+                Self::Title(e) => e.self_id,
+                Self::InputStatus(e) => e.self_id,
+                Self::GroupName(e) => e.self_id,
+                Self::Other => return None,
+            })
+        }
+
+        pub fn group_id(&self) -> Option<i64> {
+            match self {
+                Self::Poke(e) => e.group_id,
+                Self::LuckyKing(e) => Some(e.group_id),
+                Self::Honor(e) => Some(e.group_id),
+                Self::Title(e) => Some(e.group_id),
+                Self::InputStatus(e) => e.group_id.filter(|id| *id != 0),
+                Self::GroupName(e) => Some(e.group_id),
+                Self::Other => None,
+            }
+        }
+    }
+
+    /// 戳一戳; 私聊中 group_id 为 None
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct PokeNotify {
+        pub self_id: i64,
+        pub time: i64,
+        #[serde(default)]
+        pub group_id: Option<i64>,
+        pub user_id: i64,
+        pub target_id: i64,
+    }
+
+    /// 红包运气王, target_id 为运气王
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct LuckyKingNotify {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        pub user_id: i64,
+        pub target_id: i64,
+    }
+
+    /// 群荣誉变更, honor_type 为 talkative、performer、emotion 等
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct HonorNotify {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        pub user_id: i64,
+        pub honor_type: String,
+    }
+
+    /// 群头衔变更
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct TitleNotify {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        pub user_id: i64,
+        #[serde(default)]
+        pub title: String,
+    }
+
+    /// 对方正在输入 (NapCat 特有)
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct InputStatusNotify {
+        pub self_id: i64,
+        pub time: i64,
+        #[serde(default)]
+        pub group_id: Option<i64>,
+        pub user_id: i64,
+        #[serde(default)]
         pub status_text: String,
-        pub time: u64,
-        pub user_id: u64,
+        #[serde(default)]
+        pub event_type: i64,
+    }
+
+    /// 群名变更 (NapCat 特有)
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupNameNotify {
+        pub self_id: i64,
+        pub time: i64,
+        pub group_id: i64,
+        #[serde(default)]
+        pub user_id: i64,
+        #[serde(default)]
+        pub name_new: String,
+    }
+}
+
+pub mod request_event {
+    use crate::{core::action::ActionManager, types::action_type::ActionError};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    #[serde(tag = "request_type")]
+    pub enum RequestEvent {
+        #[serde(rename = "friend")]
+        Friend(FriendRequestEvent),
+        #[serde(rename = "group")]
+        Group(GroupRequestEvent),
+        #[serde(other)]
+        Other,
+    }
+
+    /// 加好友请求
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct FriendRequestEvent {
+        pub self_id: i64,
+        pub time: i64,
+        pub user_id: i64,
+        /// 验证信息
+        #[serde(default)]
+        pub comment: String,
+        /// 处理请求时需要传回的标识
+        pub flag: String,
+    }
+
+    impl FriendRequestEvent {
+        pub async fn approve(&self, act: &ActionManager) -> Result<(), ActionError> {
+            self.approve_with_remark(act, "").await
+        }
+
+        /// 同意并设置好友备注
+        pub async fn approve_with_remark(
+            &self,
+            act: &ActionManager,
+            remark: &str,
+        ) -> Result<(), ActionError> {
+            act.for_account(self.self_id)
+                .set_friend_add_request(&self.flag, true, remark)
+                .await
+        }
+
+        /// 拒绝; 好友请求无法附带理由, reason 仅记录到日志
+        pub async fn reject(&self, act: &ActionManager, reason: &str) -> Result<(), ActionError> {
+            tracing::info!("[Request] 拒绝 {} 的好友请求: {}", self.user_id, reason);
+            act.for_account(self.self_id)
+                .set_friend_add_request(&self.flag, false, "")
+                .await
+        }
+    }
+
+    /// 加群请求 (sub_type = add) 或邀请机器人入群 (sub_type = invite)
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct GroupRequestEvent {
+        pub self_id: i64,
+        pub time: i64,
+        pub sub_type: String,
+        pub group_id: i64,
+        pub user_id: i64,
+        #[serde(default)]
+        pub comment: String,
+        pub flag: String,
+    }
+
+    impl GroupRequestEvent {
+        pub fn is_invite(&self) -> bool {
+            self.sub_type == "invite"
+        }
+
+        pub async fn approve(&self, act: &ActionManager) -> Result<(), ActionError> {
+            act.for_account(self.self_id)
+                .set_group_add_request(&self.flag, &self.sub_type, true, "")
+                .await
+        }
+
+        pub async fn reject(&self, act: &ActionManager, reason: &str) -> Result<(), ActionError> {
+            act.for_account(self.self_id)
+                .set_group_add_request(&self.flag, &self.sub_type, false, reason)
+                .await
+        }
+    }
+}
+
+pub mod connection_event {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ConnectionState {
+        Connected,
+        Disconnected,
+    }
+
+    /// 适配器与 Napcat 之间的连接状态变化 (由框架产生, 非 OneBot 上报)
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct ConnectionEvent {
+        pub self_id: i64,
+        pub state: ConnectionState,
+        pub reason: String,
+        pub time: i64,
+    }
+
+    impl ConnectionEvent {
+        pub fn connected(self_id: i64) -> Self {
+            Self {
+                self_id,
+                state: ConnectionState::Connected,
+                reason: String::new(),
+                time: chrono::Utc::now().timestamp(),
+            }
+        }
+
+        pub fn disconnected(self_id: i64, reason: impl Into<String>) -> Self {
+            Self {
+                self_id,
+                state: ConnectionState::Disconnected,
+                reason: reason.into(),
+                time: chrono::Utc::now().timestamp(),
+            }
+        }
     }
 }
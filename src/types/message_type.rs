@@ -1,4 +1,135 @@
-use serde::{Deserialize, Serialize};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 图片、语音、视频与文件的来源, 发送时编码为 Napcat 的 file 参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaSource {
+    /// 本地文件, 编码为 file:// 绝对路径; 经 encode_with(true) 编码时读取后以 base64 发送
+    Path(PathBuf),
+    /// 内存中的数据, 编码为 base64://
+    Bytes(Vec<u8>),
+    /// http(s) 链接
+    Url(String),
+    /// 已按 Napcat 约定编码的字符串 (file://, base64://, 文件 ID 等), 原样发送
+    Raw(String),
+}
+
+impl MediaSource {
+    pub fn path(path: impl Into<PathBuf>) -> Self {
+        Self::Path(path.into())
+    }
+
+    pub fn bytes(data: impl Into<Vec<u8>>) -> Self {
+        Self::Bytes(data.into())
+    }
+
+    pub fn url(url: impl Into<String>) -> Self {
+        Self::Url(url.into())
+    }
+
+    /// 编码为 file 参数
+    pub fn encode(&self) -> String {
+        match self {
+            Self::Path(path) => {
+                let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+                let path = path.to_string_lossy().replace('\\', "/");
+                if path.starts_with('/') {
+                    format!("file://{}", path)
+                } else {
+                    format!("file:///{}", path)
+                }
+            }
+            Self::Bytes(data) => format!("base64://{}", STANDARD.encode(data)),
+            Self::Url(url) | Self::Raw(url) => url.clone(),
+        }
+    }
+
+    /// 编码为 file 参数; inline 为 true 时读取本地文件并以 base64 发送, 读取失败时退回 file:// 路径
+    pub async fn encode_with(&self, inline: bool) -> String {
+        if let (true, Self::Path(path)) = (inline, self) {
+            match tokio::fs::read(path).await {
+                Ok(data) => return format!("base64://{}", STANDARD.encode(data)),
+                Err(e) => tracing::warn!("[Media] 读取 {} 失败: {}", path.display(), e),
+            }
+        }
+        self.encode()
+    }
+
+    /// encode 生成的 file:// 参数对应的本地路径
+    fn from_file_url(file: &str) -> Option<Self> {
+        let path = file.strip_prefix("file://")?;
+        // Windows 路径编码为 file:///C:/...
+        let path = match path.as_bytes() {
+            [b'/', _, b':', ..] => &path[1..],
+            _ => path,
+        };
+        Some(Self::path(path))
+    }
+
+    /// 用作上传时的默认文件名
+    pub fn file_name(&self) -> Option<String> {
+        match self {
+            Self::Path(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string()),
+            Self::Url(url) => url
+                .split(['?', '#'])
+                .next()
+                .and_then(|url| url.rsplit('/').next())
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// 字符串按前缀识别: http(s) 为链接, 其余视为已编码的参数
+impl From<&str> for MediaSource {
+    fn from(value: &str) -> Self {
+        if value.starts_with("http://") || value.starts_with("https://") {
+            Self::Url(value.to_string())
+        } else {
+            Self::Raw(value.to_string())
+        }
+    }
+}
+
+impl From<String> for MediaSource {
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl From<&String> for MediaSource {
+    fn from(value: &String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl From<PathBuf> for MediaSource {
+    fn from(value: PathBuf) -> Self {
+        Self::Path(value)
+    }
+}
+
+impl From<&Path> for MediaSource {
+    fn from(value: &Path) -> Self {
+        Self::Path(value.to_path_buf())
+    }
+}
+
+impl From<Vec<u8>> for MediaSource {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for MediaSource {
+    fn from(value: &[u8]) -> Self {
+        Self::Bytes(value.to_vec())
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(transparent)]
@@ -13,6 +144,12 @@ impl Message {
         }
     }
 
+    /// 将图片、语音、视频与文件消息段中的本地文件 (file://) 读取后以 base64 内联
+    pub async fn inline_local_media(mut self) -> Self {
+        inline_segments(&mut self.message).await;
+        self
+    }
+
     pub fn with_text(mut self, text: impl Into<String>) -> Self {
         self.message
             .push(MessageSegment::Text { text: text.into() });
@@ -21,14 +158,21 @@ impl Message {
 
     /// AT 某人
     pub fn with_at(mut self, id: i64) -> Self {
-        self.message.push(MessageSegment::At { qq: id });
+        self.message.push(MessageSegment::at(id));
         self
     }
 
-    /// 发送图片 (file 可以是本地路径、URL 或 base64)
-    pub fn with_image(mut self, file: impl Into<String>) -> Self {
-        self.message
-            .push(MessageSegment::Image { file: file.into() });
+    /// AT 全体成员
+    pub fn with_at_all(mut self) -> Self {
+        self.message.push(MessageSegment::at_all());
+        self
+    }
+
+    /// 发送图片
+    pub fn with_image(mut self, file: impl Into<MediaSource>) -> Self {
+        self.message.push(MessageSegment::Image {
+            file: file.into().encode(),
+        });
         self
     }
 
@@ -47,16 +191,18 @@ impl Message {
     }
 
     /// 发送语音
-    pub fn with_record(mut self, file: impl Into<String>) -> Self {
-        self.message
-            .push(MessageSegment::Record { file: file.into() });
+    pub fn with_record(mut self, file: impl Into<MediaSource>) -> Self {
+        self.message.push(MessageSegment::Record {
+            file: file.into().encode(),
+        });
         self
     }
 
     /// 发送视频
-    pub fn with_video(mut self, file: impl Into<String>) -> Self {
-        self.message
-            .push(MessageSegment::Video { file: file.into() });
+    pub fn with_video(mut self, file: impl Into<MediaSource>) -> Self {
+        self.message.push(MessageSegment::Video {
+            file: file.into().encode(),
+        });
         self
     }
 
@@ -72,10 +218,33 @@ impl Message {
         self
     }
 
-    /// 发送文件 (NapCat 特有)
-    pub fn with_file(mut self, path: impl Into<String>) -> Self {
+    /// 追加另一条消息的全部消息段
+    pub fn with_message(mut self, other: Message) -> Self {
+        self.message.extend(other.message);
+        self
+    }
+
+    /// 全部消息段
+    pub fn segments(&self) -> &[MessageSegment] {
+        &self.message
+    }
+
+    /// 文本消息段的总字数
+    pub fn text_len(&self) -> usize {
         self.message
-            .push(MessageSegment::File { file: path.into() });
+            .iter()
+            .map(|segment| match segment {
+                MessageSegment::Text { text } => text.chars().count(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// 发送文件 (NapCat 特有)
+    pub fn with_file(mut self, file: impl Into<MediaSource>) -> Self {
+        self.message.push(MessageSegment::File {
+            file: file.into().encode(),
+        });
         self
     }
 }
@@ -92,8 +261,12 @@ pub enum MessageSegment {
     #[serde(rename = "text")]
     Text { text: String },
 
+    /// qq 为 QQ 号或 "all"
     #[serde(rename = "at")]
-    At { qq: i64 },
+    At {
+        #[serde(deserialize_with = "string_or_number")]
+        qq: String,
+    },
 
     #[serde(rename = "image")]
     Image { file: String },
@@ -124,6 +297,220 @@ pub enum MessageSegment {
 
     #[serde(rename = "music")]
     Music(MusicData),
+
+    /// 合并转发中的一个节点, 仅用于发送合并转发或嵌套在节点内容中
+    #[serde(rename = "node")]
+    Node(ForwardNode),
+
+    /// 收到的合并转发; Napcat 会在 content 中附带转发内容, 否则可用 get_forward_msg 获取
+    #[serde(rename = "forward")]
+    Forward {
+        #[serde(deserialize_with = "string_or_number")]
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<Vec<ForwardNode>>,
+    },
+}
+
+impl MessageSegment {
+    /// @ 某个 QQ 号
+    pub fn at(id: i64) -> Self {
+        Self::At { qq: id.to_string() }
+    }
+
+    /// @ 全体成员
+    pub fn at_all() -> Self {
+        Self::At { qq: "all".into() }
+    }
+
+    /// 被 @ 的 QQ 号; @ 全体成员或不是 At 消息段时为 None
+    pub fn at_id(&self) -> Option<i64> {
+        match self {
+            MessageSegment::At { qq } => qq.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// 内联消息段中的本地文件, 递归处理合并转发节点
+fn inline_segments(
+    segments: &mut [MessageSegment],
+) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+    Box::pin(async move {
+        for segment in segments {
+            match segment {
+                MessageSegment::Image { file }
+                | MessageSegment::Record { file }
+                | MessageSegment::Video { file }
+                | MessageSegment::File { file } => {
+                    if let Some(source) = MediaSource::from_file_url(file) {
+                        *file = source.encode_with(true).await;
+                    }
+                }
+                MessageSegment::Node(node) => inline_segments(&mut node.content).await,
+                _ => {}
+            }
+        }
+    })
+}
+
+/// 合并转发节点: 引用一条已有消息 (id), 或自定义发送者与内容
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(from = "RawForwardNode")]
+pub struct ForwardNode {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub content: Vec<MessageSegment>,
+}
+
+impl ForwardNode {
+    /// 自定义节点, 内容可包含嵌套的 Node 消息段
+    pub fn new(user_id: i64, nickname: impl Into<String>, content: Vec<MessageSegment>) -> Self {
+        Self {
+            id: None,
+            user_id: Some(user_id),
+            nickname: Some(nickname.into()),
+            content,
+        }
+    }
+
+    /// 引用一条已有消息
+    pub fn reference(message_id: impl Into<String>) -> Self {
+        Self {
+            id: Some(message_id.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// 兼容各实现上报的节点格式: node 消息段 (user_id/uin, nickname/name, content)
+/// 以及 get_forward_msg 返回的消息 (sender, message)
+#[derive(Deserialize)]
+struct RawForwardNode {
+    #[serde(default, deserialize_with = "option_string_or_number")]
+    id: Option<String>,
+    #[serde(default, alias = "uin", deserialize_with = "option_string_or_number")]
+    user_id: Option<String>,
+    #[serde(default, alias = "name")]
+    nickname: Option<String>,
+    #[serde(default)]
+    sender: Option<RawForwardSender>,
+    #[serde(default, alias = "message")]
+    content: Vec<MessageSegment>,
+}
+
+#[derive(Deserialize)]
+struct RawForwardSender {
+    #[serde(default, deserialize_with = "option_string_or_number")]
+    user_id: Option<String>,
+    #[serde(default)]
+    nickname: Option<String>,
+}
+
+impl From<RawForwardNode> for ForwardNode {
+    fn from(raw: RawForwardNode) -> Self {
+        let (sender_id, sender_name) = raw
+            .sender
+            .map(|sender| (sender.user_id, sender.nickname))
+            .unwrap_or_default();
+        // 带内容的节点按自定义节点处理, 忽略其 id
+        let has_content = !raw.content.is_empty();
+        Self {
+            id: raw.id.filter(|_| !has_content),
+            user_id: raw.user_id.or(sender_id).and_then(|id| id.parse().ok()),
+            nickname: raw.nickname.or(sender_name),
+            content: raw.content,
+        }
+    }
+}
+
+/// 合并转发消息
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct ForwardMessage {
+    messages: Vec<MessageSegment>,
+}
+
+impl ForwardMessage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 同 Message::inline_local_media, 包括嵌套节点中的消息段
+    pub async fn inline_local_media(mut self) -> Self {
+        inline_segments(&mut self.messages).await;
+        self
+    }
+
+    /// 以指定发送者身份添加一条消息
+    pub fn with_node(
+        mut self,
+        user_id: i64,
+        nickname: impl Into<String>,
+        message: Message,
+    ) -> Self {
+        self.messages.push(MessageSegment::Node(ForwardNode::new(
+            user_id,
+            nickname,
+            message.message,
+        )));
+        self
+    }
+
+    pub fn with_text_node(
+        self,
+        user_id: i64,
+        nickname: impl Into<String>,
+        text: impl Into<String>,
+    ) -> Self {
+        self.with_node(user_id, nickname, Message::new().with_text(text))
+    }
+
+    /// 添加一条嵌套的合并转发
+    pub fn with_forward(
+        mut self,
+        user_id: i64,
+        nickname: impl Into<String>,
+        forward: ForwardMessage,
+    ) -> Self {
+        self.messages.push(MessageSegment::Node(ForwardNode::new(
+            user_id,
+            nickname,
+            forward.messages,
+        )));
+        self
+    }
+
+    /// 转发一条已有消息
+    pub fn with_reference(mut self, message_id: impl Into<String>) -> Self {
+        self.messages
+            .push(MessageSegment::Node(ForwardNode::reference(message_id)));
+        self
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &ForwardNode> {
+        self.messages.iter().filter_map(|segment| match segment {
+            MessageSegment::Node(node) => Some(node),
+            _ => None,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl From<Vec<ForwardNode>> for ForwardMessage {
+    fn from(nodes: Vec<ForwardNode>) -> Self {
+        Self {
+            messages: nodes.into_iter().map(MessageSegment::Node).collect(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -143,3 +530,38 @@ pub enum MusicData {
         image: Option<String>,
     },
 }
+
+fn option_string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(i64),
+        String(String),
+        Null,
+    }
+    Ok(match Id::deserialize(deserializer)? {
+        Id::Number(id) => Some(id.to_string()),
+        Id::String(id) => Some(id),
+        Id::Null => None,
+    })
+}
+
+/// 兼容以字符串或数字上报的 ID
+pub(crate) fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(i64),
+        String(String),
+    }
+    Ok(match Id::deserialize(deserializer)? {
+        Id::Number(id) => id.to_string(),
+        Id::String(id) => id,
+    })
+}
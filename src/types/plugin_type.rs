@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use crate::{core::event::EventNexus, prelude::ActionManager};
+use crate::{
+    core::{dispatchar::Handler, event::EventNexus},
+    prelude::ActionManager,
+};
 
 #[async_trait::async_trait]
 pub trait BasePlugin: Send + Sync {
@@ -11,6 +14,10 @@ pub trait BasePlugin: Send + Sync {
         act: Arc<ActionManager>,
     ) -> ();
     async fn on_unload(self: Arc<Self>) -> ();
+    /// 交由分发器按匹配条件与优先级调用的消息处理器
+    fn handlers(self: Arc<Self>) -> Vec<Handler> {
+        Vec::new()
+    }
 }
 
 pub struct PluginWrapper {
@@ -18,6 +25,7 @@ pub struct PluginWrapper {
     description: String,
     version: String,
     author: String,
+    dry_run: bool,
     inner: Arc<dyn BasePlugin>,
 }
 
@@ -31,6 +39,7 @@ impl PluginWrapper {
             description: "None".to_string(),
             version: "0.0.0".to_string(),
             author: "None".to_string(),
+            dry_run: false,
             inner: Arc::new(plugin),
         }
     }
@@ -64,7 +73,28 @@ impl PluginWrapper {
         self
     }
 
+    /// 该插件以演练模式运行: 写操作只记录日志而不发出
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 插件的消息处理器, 名称前加上插件名
+    pub fn handlers(&self) -> Vec<Handler> {
+        self.inner
+            .clone()
+            .handlers()
+            .into_iter()
+            .map(|handler| {
+                let name = format!("{}/{}", self.name, handler.name());
+                let dry_run = self.dry_run || handler.is_dry_run();
+                handler.with_name(name).with_dry_run(dry_run)
+            })
+            .collect()
+    }
+
     pub async fn run(self: Arc<Self>, event_nexus: Arc<EventNexus>, act: Arc<ActionManager>) {
+        let act = if self.dry_run { act.dry_run() } else { act };
         self.inner.clone().on_load().await;
         loop {
             self.inner
@@ -0,0 +1,99 @@
+use meril_cat::{
+    testing::{MockResponse, TestHarness},
+    types::action_type::ActionError,
+};
+use serde_json::json;
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(1);
+
+#[tokio::test]
+async fn typed_response_is_deserialized() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "get_group_member_info",
+        MockResponse::Ok(json!({
+            "group_id": 100,
+            "user_id": 42,
+            "nickname": "tester",
+            "card": "Tester",
+            "level": 3,
+            "role": "admin",
+        })),
+    );
+    let member = harness
+        .action
+        .get_group_member_info(100, 42, false)
+        .await
+        .unwrap();
+    assert_eq!(member.card, "Tester");
+    assert_eq!(member.level, "3");
+    assert_eq!(member.role, "admin");
+
+    let sent = harness
+        .adapter
+        .wait_for_action("get_group_member_info", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(sent.params()["group_id"], 100);
+    assert_eq!(sent.params()["user_id"], 42);
+}
+
+#[tokio::test]
+async fn list_responses_are_deserialized() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "get_friend_list",
+        MockResponse::Ok(json!([
+            { "user_id": 1, "nickname": "a", "remark": "A" },
+            { "user_id": 2, "nickname": "b" },
+        ])),
+    );
+    let friends = harness.action.get_friend_list().await.unwrap();
+    assert_eq!(friends.len(), 2);
+    assert_eq!(friends[0].remark, "A");
+    assert_eq!(friends[1].remark, "");
+}
+
+#[tokio::test]
+async fn unit_actions_ignore_data() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("set_group_ban", MockResponse::Ok(json!({})));
+    harness.action.set_group_ban(100, 42, 600).await.unwrap();
+    let sent = harness
+        .adapter
+        .wait_for_action("set_group_ban", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(sent.params()["duration"], 600);
+}
+
+#[tokio::test]
+async fn failed_status_is_an_error() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "get_msg",
+        MockResponse::Failed {
+            retcode: 1200,
+            message: "message not found".into(),
+        },
+    );
+    assert_eq!(
+        harness.action.get_msg(1).await.unwrap_err().retcode(),
+        Some(1200)
+    );
+}
+
+#[tokio::test]
+async fn malformed_data_is_an_error() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("get_login_info", MockResponse::Ok(json!({ "nickname": 1 })));
+    assert!(matches!(
+        harness.action.get_login_info().await.unwrap_err(),
+        ActionError::Deserialize(_)
+    ));
+}
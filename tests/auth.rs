@@ -0,0 +1,63 @@
+use meril_cat::core::adapter::{NapcatAdapter, NapcatOptions};
+use std::net::SocketAddr;
+use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest, http::StatusCode};
+
+/// 在随机端口上提供反向 WS 服务, 返回 ws:// 地址
+async fn serve(token: &str) -> String {
+    let adapter = NapcatAdapter::with_options(NapcatOptions {
+        access_token: token.into(),
+        ..Default::default()
+    });
+    adapter.clone().run_detached();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let service = adapter
+        .router()
+        .into_make_service_with_connect_info::<SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, service).await.unwrap() });
+    format!("ws://{}/ws", addr)
+}
+
+async fn connect(url: &str, bearer: Option<&str>) -> Result<(), StatusCode> {
+    let mut request = url.into_client_request().unwrap();
+    if let Some(token) = bearer {
+        let value = format!("Bearer {}", token).parse().unwrap();
+        request.headers_mut().insert("Authorization", value);
+    }
+    match tokio_tungstenite::connect_async(request).await {
+        Ok(_) => Ok(()),
+        Err(tungstenite::Error::Http(response)) => Err(response.status()),
+        Err(e) => panic!("unexpected error: {}", e),
+    }
+}
+
+#[tokio::test]
+async fn missing_or_wrong_token_is_rejected() {
+    let url = serve("secret").await;
+    assert_eq!(connect(&url, None).await, Err(StatusCode::UNAUTHORIZED));
+    assert_eq!(
+        connect(&url, Some("wrong")).await,
+        Err(StatusCode::UNAUTHORIZED)
+    );
+    // 前缀相同也不行
+    assert_eq!(
+        connect(&url, Some("secret2")).await,
+        Err(StatusCode::UNAUTHORIZED)
+    );
+    let query = format!("{}?access_token=secre", url);
+    assert_eq!(connect(&query, None).await, Err(StatusCode::UNAUTHORIZED));
+}
+
+#[tokio::test]
+async fn token_is_accepted_from_header_or_query() {
+    let url = serve("secret").await;
+    assert_eq!(connect(&url, Some("secret")).await, Ok(()));
+    let query = format!("{}?access_token=secret", url);
+    assert_eq!(connect(&query, None).await, Ok(()));
+}
+
+#[tokio::test]
+async fn empty_token_disables_auth() {
+    let url = serve("").await;
+    assert_eq!(connect(&url, None).await, Ok(()));
+}
@@ -0,0 +1,85 @@
+use meril_cat::{
+    plugins::recall::RecallPlugin,
+    prelude::{MerilBot, Message, PluginWrapper},
+    testing::{MockAdapter, TEST_SELF_ID},
+};
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+
+const WAIT: Duration = Duration::from_secs(1);
+const ADMIN: i64 = 42;
+
+/// 以 MockAdapter 启动 MerilBot, 启动前可注册插件
+async fn start(plugins: Vec<PluginWrapper>) -> (Arc<MockAdapter>, MerilBot) {
+    let adapter = MockAdapter::new();
+    let bot = MerilBot::with_adapter(adapter.clone());
+    for plugin in plugins {
+        bot.register_plugin(plugin).await;
+    }
+    (adapter, bot)
+}
+
+/// 在后台运行; 测试运行时为单线程, 让出几次后插件均已加载, 分发器开始监听
+async fn run(bot: MerilBot) {
+    tokio::spawn(async move { bot.run().await });
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn recall_plugin_is_opt_in() {
+    let (adapter, bot) = start(vec![]).await;
+    let act = bot.action.clone();
+    run(bot).await;
+    act.send_group_message(100, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+    adapter.push_group_message(100, ADMIN, "/recall");
+    assert!(
+        adapter
+            .wait_for_action("delete_msg", 0, Duration::from_millis(200))
+            .await
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn registered_recall_plugin_handles_command() {
+    let recall = PluginWrapper::new(RecallPlugin::new(ADMIN)).with_name("Recall");
+    let (adapter, bot) = start(vec![recall]).await;
+    let act = bot.action.clone();
+    run(bot).await;
+    let sent = act
+        .send_group_message(100, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+    adapter.push_group_message(100, ADMIN, "/recall");
+    let data = adapter
+        .wait_for_action("delete_msg", 0, WAIT)
+        .await
+        .expect("delete_msg");
+    assert_eq!(data.params()["message_id"], sent.message_id);
+    assert_eq!(data.self_id(), Some(TEST_SELF_ID));
+}
+
+#[tokio::test]
+async fn request_plugin_is_opt_in() {
+    let (adapter, bot) = start(vec![]).await;
+    run(bot).await;
+    adapter.push_event(json!({
+        "post_type": "request",
+        "request_type": "friend",
+        "self_id": TEST_SELF_ID,
+        "time": 0,
+        "user_id": 7,
+        "comment": "hi",
+        "flag": "f1",
+    }));
+    assert!(
+        adapter
+            .wait_for_action("send_private_msg", 0, Duration::from_millis(200))
+            .await
+            .is_none()
+    );
+}
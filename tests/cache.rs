@@ -0,0 +1,139 @@
+use meril_cat::{
+    core::cache::InfoCache,
+    testing::{MockResponse, TEST_SELF_ID, TestHarness},
+};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+fn member_list() -> MockResponse {
+    MockResponse::Ok(json!([
+        { "group_id": 100, "user_id": 1, "nickname": "one", "card": "", "role": "owner" },
+        { "group_id": 100, "user_id": 2, "nickname": "two", "card": "Two", "role": "member" },
+    ]))
+}
+
+fn notice(notice_type: &str, extra: Value) -> Value {
+    let mut event = json!({
+        "post_type": "notice",
+        "notice_type": notice_type,
+        "self_id": TEST_SELF_ID,
+        "time": 0,
+    });
+    event
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    event
+}
+
+async fn count(harness: &TestHarness, action: &str) -> usize {
+    harness
+        .adapter
+        .sent_actions()
+        .await
+        .iter()
+        .filter(|data| data.action() == action)
+        .count()
+}
+
+#[tokio::test]
+async fn lists_are_fetched_once() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("get_group_member_list", member_list());
+    let act = harness.action.for_account(TEST_SELF_ID);
+    assert_eq!(
+        act.cached_display_name(100, 1).await.as_deref(),
+        Some("one")
+    );
+    assert_eq!(
+        act.cached_display_name(100, 2).await.as_deref(),
+        Some("Two")
+    );
+    assert_eq!(act.cached_member_list(100).await.unwrap().len(), 2);
+    assert_eq!(count(&harness, "get_group_member_list").await, 1);
+}
+
+#[tokio::test]
+async fn notices_update_cache() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("get_group_member_list", member_list());
+    harness.adapter.respond(
+        "get_group_list",
+        MockResponse::Ok(json!([{ "group_id": 100, "group_name": "old", "member_count": 2 }])),
+    );
+    let act = harness.action.for_account(TEST_SELF_ID);
+    act.cached_member_list(100).await.unwrap();
+    act.cached_group_list().await.unwrap();
+
+    harness.adapter.push_event(notice(
+        "group_card",
+        json!({ "group_id": 100, "user_id": 1, "card_new": "One", "card_old": "" }),
+    ));
+    harness.adapter.push_event(notice(
+        "group_admin",
+        json!({ "group_id": 100, "user_id": 2, "sub_type": "set" }),
+    ));
+    harness.adapter.push_event(notice(
+        "group_decrease",
+        json!({ "group_id": 100, "user_id": 1, "operator_id": 1, "sub_type": "leave" }),
+    ));
+    harness.adapter.push_event(notice(
+        "notify",
+        json!({ "group_id": 100, "user_id": 2, "sub_type": "group_name", "name_new": "new" }),
+    ));
+    harness.settle().await;
+
+    assert!(act.cached_member(100, 1).await.unwrap().is_none());
+    assert_eq!(
+        act.cached_member(100, 2).await.unwrap().unwrap().role,
+        "admin"
+    );
+    let group = act.cached_group(100).await.unwrap().unwrap();
+    assert_eq!(group.group_name, "new");
+    assert_eq!(group.member_count, 1);
+    assert_eq!(count(&harness, "get_group_member_list").await, 1);
+    assert_eq!(count(&harness, "get_group_list").await, 1);
+}
+
+#[tokio::test]
+async fn increase_invalidates_member_list() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("get_group_member_list", member_list());
+    let act = harness.action.for_account(TEST_SELF_ID);
+    act.cached_member_list(100).await.unwrap();
+    harness.adapter.push_event(notice(
+        "group_increase",
+        json!({ "group_id": 100, "user_id": 3, "operator_id": 0, "sub_type": "approve" }),
+    ));
+    harness.settle().await;
+    harness.adapter.respond(
+        "get_group_member_list",
+        MockResponse::Ok(json!([
+            { "group_id": 100, "user_id": 3, "nickname": "three" },
+        ])),
+    );
+    assert!(act.cached_member(100, 3).await.unwrap().is_some());
+    assert_eq!(count(&harness, "get_group_member_list").await, 2);
+}
+
+#[tokio::test]
+async fn entries_expire_after_ttl() {
+    let harness = TestHarness::new();
+    let cache = InfoCache::new(Duration::from_millis(50));
+    let friends = || MockResponse::Ok(json!([{ "user_id": 1, "nickname": "one" }]));
+    harness.adapter.respond("get_friend_list", friends());
+    harness.adapter.respond("get_friend_list", friends());
+    let len = |friends: &std::collections::HashMap<i64, _>| friends.len();
+    assert_eq!(cache.friends(&harness.action, len).await.unwrap(), 1);
+    assert_eq!(cache.friends(&harness.action, len).await.unwrap(), 1);
+    assert_eq!(count(&harness, "get_friend_list").await, 1);
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    assert_eq!(cache.friends(&harness.action, len).await.unwrap(), 1);
+    assert_eq!(count(&harness, "get_friend_list").await, 2);
+}
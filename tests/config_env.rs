@@ -0,0 +1,80 @@
+use meril_cat::{
+    config::{Config, RateLimit},
+    core::adapter::{NapcatAdapter, NapcatOptions},
+    prelude::MerilBot,
+};
+use std::{net::SocketAddr, sync::Once};
+use tokio_tungstenite::tungstenite::{self, http::StatusCode};
+
+/// 每个测试二进制只初始化一次 Config, 须在首次读取前设置好全部环境变量
+fn config() -> &'static Config {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| {
+        let vars = [
+            ("MERIL_RATE_LIMIT_GLOBAL", "5/10"),
+            ("MERIL_RATE_LIMIT_GROUP", "0.5/3"),
+            ("MERIL_RATE_LIMIT_USER", "not a limit"),
+            ("MERIL_TYPING_DELAY_PER_CHAR", "50"),
+            ("MERIL_TYPING_DELAY_MAX", "2000"),
+            ("NAPCAT_WEBSOCKET_TOKEN", "secret"),
+            ("MERIL_ROOT_ID", "42"),
+            ("MERIL_REPLAY_FILE", "/nonexistent/meril-replay.jsonl"),
+        ];
+        for (key, value) in vars {
+            // SAFETY: 在任何线程读取环境变量之前, 由 Once 保证只执行一次
+            unsafe { std::env::set_var(key, value) };
+        }
+    });
+    Config::get_or_init()
+}
+
+#[test]
+fn rate_limits_are_read_from_env() {
+    let config = config();
+    assert_eq!(*config.rate_limit_global(), RateLimit::new(5.0, 10));
+    assert_eq!(*config.rate_limit_group(), RateLimit::new(0.5, 3));
+    // 格式错误时不限速
+    assert_eq!(*config.rate_limit_user(), RateLimit::UNLIMITED);
+    assert_eq!(*config.typing_delay_per_char(), 50);
+    assert_eq!(*config.typing_delay_max(), 2000);
+}
+
+#[tokio::test]
+async fn websocket_token_is_read_from_env() {
+    config();
+    let options = NapcatOptions::from_config();
+    assert_eq!(options.access_token, "secret");
+
+    let adapter = NapcatAdapter::with_options(options);
+    adapter.clone().run_detached();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let service = adapter
+        .router()
+        .into_make_service_with_connect_info::<SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, service).await.unwrap() });
+
+    match tokio_tungstenite::connect_async(url.as_str()).await {
+        Err(tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED)
+        }
+        other => panic!("expected 401, got {:?}", other.map(|_| ())),
+    }
+    let authorized = format!("{}?access_token=secret", url);
+    assert!(tokio_tungstenite::connect_async(authorized).await.is_ok());
+}
+
+#[test]
+fn root_id_is_read_from_env() {
+    assert_eq!(*config().root_id(), 42);
+}
+
+#[test]
+fn missing_replay_file_is_an_error() {
+    config();
+    let err = MerilBot::try_new()
+        .err()
+        .expect("replay file does not exist");
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("meril-replay.jsonl"));
+}
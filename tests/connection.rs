@@ -0,0 +1,122 @@
+use meril_cat::{
+    core::adapter::{NapcatAdapter, NapcatOptions},
+    prelude::{ActionManager, Adapter, Message},
+    testing::{MockResponse, NapcatPeer, TestHarness},
+    types::{action_type::ActionError, event_type::connection_event::ConnectionState},
+};
+use std::time::{Duration, Instant};
+
+const WAIT: Duration = Duration::from_secs(1);
+/// 远大于测试等待时间, 请求若失败必然是因为断线而非超时
+const LONG_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[tokio::test]
+async fn disconnect_fails_in_flight_request_of_that_account_only() {
+    let harness = TestHarness::new();
+    harness.adapter.respond("get_status", MockResponse::NoReply);
+    harness.adapter.respond("get_status", MockResponse::NoReply);
+    let a = harness.action.for_account(10001).with_timeout(LONG_TIMEOUT);
+    let b = harness.action.for_account(10002).with_timeout(LONG_TIMEOUT);
+    let request_a = tokio::spawn(async move { a.get_status().await });
+    let request_b = tokio::spawn(async move { b.get_status().await });
+    harness
+        .adapter
+        .wait_for_action("get_status", 1, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(harness.action.pending_count(), 2);
+
+    let started = Instant::now();
+    harness.adapter.disconnect(10001, "test");
+    let res = tokio::time::timeout(WAIT, request_a)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        res.unwrap_err(),
+        ActionError::TransportClosed("Connection Lost".into())
+    );
+    assert!(started.elapsed() < WAIT);
+    // 另一个账号的请求不受影响
+    assert_eq!(harness.action.pending_count(), 1);
+    assert!(!request_b.is_finished());
+
+    harness.adapter.disconnect(10002, "test");
+    let res = tokio::time::timeout(WAIT, request_b)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(res, Err(ActionError::TransportClosed(_))));
+    assert_eq!(harness.action.pending_count(), 0);
+}
+
+#[tokio::test]
+async fn disconnect_fails_unscoped_requests() {
+    let harness = TestHarness::new();
+    harness.adapter.respond("get_status", MockResponse::NoReply);
+    let act = harness.action.with_timeout(LONG_TIMEOUT);
+    let request = tokio::spawn(async move { act.get_status().await });
+    harness
+        .adapter
+        .wait_for_action("get_status", 0, WAIT)
+        .await
+        .unwrap();
+    harness.adapter.disconnect(10001, "test");
+    let res = tokio::time::timeout(WAIT, request).await.unwrap().unwrap();
+    assert!(matches!(res, Err(ActionError::TransportClosed(_))));
+}
+
+#[tokio::test]
+async fn connection_states_reach_event_nexus() {
+    let harness = TestHarness::new();
+    let port = harness.event_nexus.get_connection_port();
+    harness.adapter.connect(10001);
+    harness.adapter.disconnect(10001, "socket closed");
+
+    let connected = tokio::time::timeout(WAIT, port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(connected.self_id, 10001);
+    assert_eq!(connected.state, ConnectionState::Connected);
+    let disconnected = tokio::time::timeout(WAIT, port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(disconnected.state, ConnectionState::Disconnected);
+    assert_eq!(disconnected.reason, "socket closed");
+}
+
+#[tokio::test]
+async fn socket_close_fails_in_flight_request() {
+    let adapter = NapcatAdapter::with_options(NapcatOptions::default());
+    adapter.clone().run_detached();
+    let act =
+        ActionManager::with_http_client(adapter.get_action_port(), adapter.get_state_port(), None);
+    act.clone().run();
+    let states = adapter.get_state_port();
+    let mut napcat = NapcatPeer::connect(&adapter).await;
+    napcat.hello(10001).await;
+    tokio::time::timeout(WAIT, states.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let scoped = act.for_account(10001).with_timeout(LONG_TIMEOUT);
+    let request = tokio::spawn(async move {
+        scoped
+            .send_private_message(42, Message::new().with_text("hi"))
+            .await
+    });
+    napcat.recv(WAIT).await.expect("action reaches napcat");
+    napcat.close().await;
+
+    let res = tokio::time::timeout(WAIT, request).await.unwrap().unwrap();
+    assert!(matches!(res, Err(ActionError::TransportClosed(_))));
+    let state = tokio::time::timeout(WAIT, states.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.state, ConnectionState::Disconnected);
+    assert_eq!(state.self_id, 10001);
+}
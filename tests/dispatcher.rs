@@ -0,0 +1,246 @@
+use meril_cat::{
+    core::dispatchar::{Dispatcher, Handler, Matcher},
+    plugins::ai_chat::AiChatPlugin,
+    prelude::Message,
+    testing::{TEST_SELF_ID, TestHarness, group_message, private_message},
+    types::event_type::message_event::MessageEvent,
+};
+use serde_json::{Value, json};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const WAIT: Duration = Duration::from_secs(1);
+
+fn event(value: Value) -> Arc<MessageEvent> {
+    Arc::new(serde_json::from_value(value).unwrap())
+}
+
+fn at_self_message(group_id: i64, user_id: i64, text: &str) -> Value {
+    let mut value = group_message(group_id, user_id, text);
+    value["message"] = json!([
+        { "type": "at", "data": { "qq": TEST_SELF_ID.to_string() } },
+        { "type": "text", "data": { "text": format!(" {}", text) } },
+    ]);
+    value
+}
+
+fn noop(name: &str) -> Handler {
+    Handler::new(name, |_, _| async {})
+}
+
+#[test]
+fn matchers_match_expected_messages() {
+    let private = event(private_message(42, "  /mood now "));
+    let group = event(group_message(7, 42, "今天天气不错"));
+    let at_self = event(at_self_message(7, 43, "hello"));
+
+    assert!(Matcher::command("/mood").matches(&private));
+    assert!(!Matcher::command("/mo").matches(&private));
+    assert!(Matcher::prefix("/mo").matches(&private));
+    assert!(Matcher::regex(r"^/mood\s+\w+$").unwrap().matches(&private));
+    assert!(Matcher::regex("(").is_err());
+    assert!(Matcher::keyword("天气").matches(&group));
+    assert!(!Matcher::keyword("天气").matches(&private));
+
+    assert!(Matcher::Private.matches(&private));
+    assert!(!Matcher::Private.matches(&group));
+    assert!(Matcher::Group.matches(&group));
+    assert!(Matcher::Groups(vec![7]).matches(&group));
+    assert!(!Matcher::Groups(vec![8]).matches(&group));
+    assert!(!Matcher::Groups(vec![7]).matches(&private));
+    assert!(Matcher::Users(vec![42]).matches(&private));
+    assert!(!Matcher::Users(vec![42]).matches(&at_self));
+
+    assert!(Matcher::AtBot.matches(&at_self));
+    assert!(!Matcher::AtBot.matches(&group));
+    assert!(Matcher::ToMe.matches(&private));
+    assert!(Matcher::ToMe.matches(&at_self));
+    assert!(!Matcher::ToMe.matches(&group));
+    // @ 消息段不计入纯文本
+    assert!(Matcher::command("hello").matches(&at_self));
+
+    let custom = Matcher::custom(|event| event.sender().user_id > 42);
+    assert!(custom.matches(&at_self));
+    assert!(!custom.matches(&private));
+}
+
+#[test]
+fn handler_requires_all_matchers() {
+    let handler = noop("h")
+        .with_matcher(Matcher::Group)
+        .with_matcher(Matcher::keyword("天气"));
+    assert!(handler.matches(&event(group_message(7, 42, "天气"))));
+    assert!(!handler.matches(&event(private_message(42, "天气"))));
+    assert!(!handler.matches(&event(group_message(7, 42, "hello"))));
+    assert!(noop("any").matches(&event(private_message(42, "hello"))));
+}
+
+#[tokio::test]
+async fn dispatch_runs_by_priority_and_stops_after_block() {
+    let harness = TestHarness::new();
+    let dispatcher = Dispatcher::new();
+    let order = Arc::new(Mutex::new(Vec::new()));
+    for (name, priority, block) in [
+        ("late", 100, false),
+        ("first", 10, false),
+        ("blocker", 20, true),
+        ("same_priority", 20, false),
+        ("skipped", 30, false),
+    ] {
+        let order = order.clone();
+        dispatcher.register(
+            Handler::new(name, move |_, _| {
+                let order = order.clone();
+                async move {
+                    order.lock().unwrap().push(name);
+                }
+            })
+            .with_priority(priority)
+            .with_block(block),
+        );
+    }
+    assert_eq!(dispatcher.len(), 5);
+
+    let handled = dispatcher
+        .dispatch(event(private_message(42, "hi")), harness.action.clone())
+        .await;
+    assert_eq!(handled, ["first", "blocker", "same_priority"]);
+    assert_eq!(
+        *order.lock().unwrap(),
+        ["first", "blocker", "same_priority"]
+    );
+}
+
+#[tokio::test]
+async fn unmatched_blocker_does_not_stop_propagation() {
+    let harness = TestHarness::new();
+    let dispatcher = Dispatcher::new();
+    dispatcher.register(
+        noop("command")
+            .with_matcher(Matcher::command("/mood"))
+            .with_priority(10)
+            .with_block(true),
+    );
+    dispatcher.register(noop("fallback").with_priority(100));
+
+    let handled = dispatcher
+        .dispatch(event(private_message(42, "hello")), harness.action.clone())
+        .await;
+    assert_eq!(handled, ["fallback"]);
+    let handled = dispatcher
+        .dispatch(event(private_message(42, "/mood")), harness.action.clone())
+        .await;
+    assert_eq!(handled, ["command"]);
+}
+
+#[tokio::test]
+async fn dry_run_handler_does_not_send() {
+    let harness = TestHarness::new();
+    let dispatcher = Dispatcher::new();
+    dispatcher.register(
+        Handler::new("echo", |event, act| async move {
+            let _ = act
+                .send_private_message(event.sender().user_id, Message::new().with_text("echo"))
+                .await;
+        })
+        .with_dry_run(true),
+    );
+
+    let handled = dispatcher
+        .dispatch(event(private_message(42, "hi")), harness.action.clone())
+        .await;
+    assert_eq!(handled, ["echo"]);
+    assert!(harness.adapter.sent_actions().await.is_empty());
+}
+
+#[tokio::test]
+async fn harness_dispatches_incoming_messages() {
+    let harness = TestHarness::new();
+    harness.dispatcher.register(
+        Handler::new("echo", |event, act| async move {
+            let _ = act
+                .for_account(event.self_id())
+                .send_group_message(event.group_id().unwrap(), Message::new().with_text("pong"))
+                .await;
+        })
+        .with_matcher(Matcher::Groups(vec![7]))
+        .with_matcher(Matcher::command("/ping")),
+    );
+    harness.adapter.push_group_message(8, 42, "/ping");
+    harness.adapter.push_group_message(7, 42, "/ping");
+
+    let sent = harness
+        .adapter
+        .wait_for_action("send_group_msg", 0, WAIT)
+        .await
+        .expect("pong");
+    assert_eq!(sent.params()["group_id"], 7);
+    harness.settle().await;
+    assert_eq!(harness.adapter.sent_actions().await.len(), 1);
+}
+
+#[tokio::test]
+async fn mood_command_does_not_reach_free_chat() {
+    let harness = TestHarness::new();
+    harness.load_plugin(AiChatPlugin::new("")).await;
+    let chatted = Arc::new(Mutex::new(0));
+    let counter = chatted.clone();
+    // 与闲聊同优先级、同条件的替身, 用于观察 /mood 是否继续传播
+    harness.dispatcher.register(
+        Handler::new("free_chat_probe", move |_, _| {
+            let counter = counter.clone();
+            async move {
+                *counter.lock().unwrap() += 1;
+            }
+        })
+        .with_matcher(Matcher::Private)
+        .with_priority(100),
+    );
+
+    harness.adapter.push_private_message(42, "/mood");
+    harness
+        .adapter
+        .wait_for_action("send_private_msg", 0, WAIT)
+        .await
+        .expect("mood reply");
+    harness.settle().await;
+    assert_eq!(*chatted.lock().unwrap(), 0);
+
+    harness.adapter.push_private_message(42, "hello");
+    harness.settle().await;
+    assert_eq!(*chatted.lock().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn shutdown_stops_run() {
+    let harness = TestHarness::new();
+    let dispatcher = Dispatcher::new();
+    dispatcher.register(
+        Handler::new("ping", |event, act| async move {
+            let _ = act
+                .for_account(event.self_id())
+                .send_private_message(event.sender().user_id, Message::new().with_text("pong"))
+                .await;
+        })
+        .with_matcher(Matcher::command("/ping")),
+    );
+    dispatcher
+        .clone()
+        .run(harness.event_nexus.clone(), harness.action.clone());
+    tokio::task::yield_now().await;
+
+    harness.adapter.push_private_message(42, "/ping");
+    harness
+        .adapter
+        .wait_for_action("send_private_msg", 0, WAIT)
+        .await
+        .expect("pong");
+
+    dispatcher.shutdown();
+    tokio::task::yield_now().await;
+    harness.adapter.push_private_message(42, "/ping");
+    harness.settle().await;
+    assert_eq!(harness.adapter.sent_actions().await.len(), 1);
+}
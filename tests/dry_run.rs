@@ -0,0 +1,72 @@
+use meril_cat::{
+    plugins::get_help::HelpPlugin,
+    prelude::{Message, PluginWrapper},
+    testing::{MockResponse, TestHarness},
+};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[tokio::test]
+async fn write_actions_are_not_sent() {
+    let harness = TestHarness::new();
+    let act = harness.action.dry_run();
+    assert!(act.is_dry_run());
+    assert!(!harness.action.is_dry_run());
+
+    let sent = act
+        .send_group_message(100, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+    assert!(sent.message_id < 0);
+    act.set_group_kick(100, 42, false).await.unwrap();
+    act.set_group_ban(100, 42, 60).await.unwrap();
+    sent.recall(&act).await.unwrap();
+
+    assert!(harness.adapter.sent_actions().await.is_empty());
+}
+
+#[tokio::test]
+async fn read_actions_still_go_through() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "get_login_info",
+        MockResponse::Ok(json!({ "user_id": 10000, "nickname": "bot" })),
+    );
+    let act = harness.action.dry_run();
+    let info = act.get_login_info().await.unwrap();
+    assert_eq!(info.nickname, "bot");
+
+    let sent = harness.adapter.sent_actions().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].action(), "get_login_info");
+}
+
+#[tokio::test]
+async fn dry_run_survives_scoping() {
+    let harness = TestHarness::new();
+    let act = harness.action.dry_run().for_account(1);
+    assert!(act.is_dry_run());
+    act.send_like(42, 1).await.unwrap();
+    assert!(harness.adapter.sent_actions().await.is_empty());
+}
+
+#[tokio::test]
+async fn plugin_can_run_in_dry_run() {
+    let harness = TestHarness::new();
+    let plugin = HelpPlugin::new(Arc::new(RwLock::new(Vec::new())));
+    harness
+        .load_wrapper(PluginWrapper::new(plugin).with_dry_run(true))
+        .await;
+    harness.adapter.push_private_message(42, "/help");
+    harness.settle().await;
+    assert!(harness.adapter.sent_actions().await.is_empty());
+
+    // 其他插件不受影响
+    harness
+        .action
+        .send_private_message(42, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+    assert_eq!(harness.adapter.sent_actions().await.len(), 1);
+}
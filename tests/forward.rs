@@ -0,0 +1,99 @@
+use meril_cat::{
+    prelude::{ForwardMessage, Message},
+    testing::{MockResponse, TestHarness},
+    types::message_type::MessageSegment,
+};
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn builder_serializes_nodes() {
+    let inner = ForwardMessage::new().with_text_node(2, "inner", "nested");
+    let forward = ForwardMessage::new()
+        .with_text_node(1, "bot", "hello")
+        .with_node(1, "bot", Message::new().with_image("a.png"))
+        .with_forward(1, "bot", inner)
+        .with_reference("123");
+    let value = serde_json::to_value(&forward).unwrap();
+    assert_eq!(
+        value,
+        json!([
+            { "type": "node", "data": { "user_id": 1, "nickname": "bot",
+                "content": [{ "type": "text", "data": { "text": "hello" } }] } },
+            { "type": "node", "data": { "user_id": 1, "nickname": "bot",
+                "content": [{ "type": "image", "data": { "file": "a.png" } }] } },
+            { "type": "node", "data": { "user_id": 1, "nickname": "bot",
+                "content": [{ "type": "node", "data": { "user_id": 2, "nickname": "inner",
+                    "content": [{ "type": "text", "data": { "text": "nested" } }] } }] } },
+            { "type": "node", "data": { "id": "123" } },
+        ])
+    );
+}
+
+#[test]
+fn incoming_forward_segment_is_parsed() {
+    let segment: MessageSegment = serde_json::from_value(json!({
+        "type": "forward",
+        "data": {
+            "id": "7412",
+            "content": [{
+                "message_id": 1,
+                "sender": { "user_id": 42, "nickname": "tester" },
+                "message": [{ "type": "text", "data": { "text": "hi" } }],
+            }],
+        },
+    }))
+    .unwrap();
+    let MessageSegment::Forward { id, content } = segment else {
+        panic!("not a forward segment");
+    };
+    assert_eq!(id, "7412");
+    let content = content.unwrap();
+    assert_eq!(content[0].user_id, Some(42));
+    assert_eq!(content[0].nickname.as_deref(), Some("tester"));
+    assert_eq!(content[0].content.len(), 1);
+}
+
+#[tokio::test]
+async fn send_and_read_forward() {
+    let harness = TestHarness::new();
+    let forward = ForwardMessage::new().with_text_node(1, "bot", "hello");
+    harness
+        .action
+        .send_group_forward_msg(100, forward)
+        .await
+        .unwrap();
+    let sent = harness
+        .adapter
+        .wait_for_action("send_group_forward_msg", 0, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(sent.params()["group_id"], 100);
+    assert_eq!(sent.params()["messages"][0]["type"], "node");
+
+    harness.adapter.respond(
+        "get_forward_msg",
+        MockResponse::Ok(json!({
+            "messages": [
+                {
+                    "sender": { "user_id": 42, "nickname": "tester" },
+                    "message": [{ "type": "text", "data": { "text": "one" } }],
+                },
+                {
+                    "user_id": "43",
+                    "nickname": "other",
+                    "content": [{ "type": "forward", "data": { "id": "inner" } }],
+                },
+            ],
+        })),
+    );
+    let forward = harness.action.get_forward_msg("7412").await.unwrap();
+    let nodes: Vec<_> = forward.nodes().collect();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0].user_id, Some(42));
+    assert_eq!(nodes[1].user_id, Some(43));
+    assert!(matches!(
+        nodes[1].content[0],
+        MessageSegment::Forward { .. }
+    ));
+}
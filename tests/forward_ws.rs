@@ -0,0 +1,107 @@
+use meril_cat::{
+    core::adapter::{Backoff, NapcatAdapter, NapcatOptions},
+    prelude::Adapter,
+    testing::NapcatPeer,
+    types::signal_type::SignalPort,
+};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+const WAIT: Duration = Duration::from_secs(1);
+
+#[test]
+fn backoff_doubles_up_to_max_and_resets() {
+    let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(500));
+    let delays: Vec<_> = (0..5).map(|_| backoff.next_delay().as_millis()).collect();
+    assert_eq!(delays, [100, 200, 400, 500, 500]);
+    backoff.reset();
+    assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    // max 小于 initial 时以 initial 为准
+    let mut backoff = Backoff::new(Duration::from_secs(2), Duration::from_secs(1));
+    assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+    assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+}
+
+#[test]
+fn forward_request_carries_bearer_token() {
+    let request = NapcatAdapter::build_forward_request("ws://127.0.0.1:3001", "secret").unwrap();
+    assert_eq!(request.uri(), "ws://127.0.0.1:3001/");
+    assert_eq!(request.headers()["Authorization"], "Bearer secret");
+
+    let request = NapcatAdapter::build_forward_request("ws://127.0.0.1:3001", "").unwrap();
+    assert!(request.headers().get("Authorization").is_none());
+
+    assert!(NapcatAdapter::build_forward_request("not a url", "").is_err());
+    assert!(NapcatAdapter::build_forward_request("ws://127.0.0.1", "bad\ntoken").is_err());
+}
+
+/// 等待下一条由适配器注入的 lifecycle 事件
+async fn next_lifecycle(port: &SignalPort<Value>) -> (String, i64) {
+    loop {
+        let event = tokio::time::timeout(WAIT, port.recv())
+            .await
+            .expect("lifecycle")
+            .unwrap();
+        if event["meta_event_type"] == "lifecycle" && event["sub_type"] != "connect" {
+            return (
+                event["sub_type"].as_str().unwrap().to_string(),
+                event["self_id"].as_i64().unwrap(),
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn reconnect_emits_enable_after_disable() {
+    let adapter = NapcatAdapter::with_options(NapcatOptions::default());
+    adapter.clone().run_detached();
+    let events = adapter.get_event_port();
+
+    let mut napcat = NapcatPeer::connect(&adapter).await;
+    napcat.hello(10001).await;
+    assert_eq!(next_lifecycle(&events).await, ("enable".into(), 10001));
+
+    napcat.close().await;
+    assert_eq!(next_lifecycle(&events).await, ("disable".into(), 10001));
+
+    let mut napcat = NapcatPeer::connect(&adapter).await;
+    napcat.hello(10001).await;
+    assert_eq!(next_lifecycle(&events).await, ("enable".into(), 10001));
+}
+
+#[tokio::test]
+async fn forward_connection_sends_access_token() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let adapter = NapcatAdapter::with_options(NapcatOptions {
+        access_token: "secret".into(),
+        ..Default::default()
+    });
+    adapter.clone().run_detached();
+    tokio::spawn(adapter.clone().connect_forward_to(url));
+
+    let (mut stream, _) = tokio::time::timeout(WAIT, listener.accept())
+        .await
+        .unwrap()
+        .unwrap();
+    // 只需检查握手请求头
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let n = tokio::time::timeout(WAIT, stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(n > 0, "connection closed during handshake");
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request = String::from_utf8(request).unwrap();
+    assert!(
+        request
+            .lines()
+            .any(|line| line.eq_ignore_ascii_case("authorization: Bearer secret")),
+        "{}",
+        request
+    );
+}
@@ -0,0 +1,160 @@
+use axum::{
+    Json, Router,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    routing::post,
+};
+use meril_cat::{
+    core::{
+        action::HttpActionClient,
+        limiter::Conversation,
+        record::{RecordKind, RecordingAdapter, read_recording},
+    },
+    prelude::{ActionManager, Adapter, Message},
+    testing::MockAdapter,
+    types::{action_type::ActionError, signal_type::SignalHub},
+};
+use serde_json::{Value, json};
+use std::{sync::Arc, time::Duration};
+
+async fn spawn_napcat_stand_in() -> String {
+    let router = Router::new().route(
+        "/send_private_msg",
+        post(|headers: HeaderMap, Json(params): Json<Value>| async move {
+            let authorized = headers
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                == Some("Bearer secret");
+            if !authorized {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            Ok(Json(json!({
+                "status": "ok",
+                "retcode": 0,
+                "data": { "message_id": 42, "user_id": params["user_id"] },
+                "message": "",
+                "wording": "",
+            })))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn http_action_manager(base_url: &str, token: &str) -> Arc<ActionManager> {
+    let ws_hub = SignalHub::new();
+    let state_hub = SignalHub::new();
+    ActionManager::with_http_client(
+        ws_hub.get_port(),
+        state_hub.get_port(),
+        Some(HttpActionClient::new(base_url, token)),
+    )
+}
+
+#[tokio::test]
+async fn send_private_message_over_http() {
+    let base_url = spawn_napcat_stand_in().await;
+    let act = http_action_manager(&base_url, "secret");
+    let res = act
+        .send_private_message(10001, Message::new().with_text("hello"))
+        .await
+        .unwrap();
+    assert_eq!(res.message_id, 42);
+    assert_eq!(res.target, Conversation::Private(10001));
+}
+
+#[tokio::test]
+async fn wrong_token_is_rejected() {
+    let base_url = spawn_napcat_stand_in().await;
+    let act = http_action_manager(&base_url, "wrong");
+    let res = act
+        .send_private_message(10001, Message::new().with_text("hello"))
+        .await;
+    assert_eq!(
+        res.unwrap_err(),
+        ActionError::Transport("unauthorized".into())
+    );
+}
+
+async fn spawn_account_stand_in(message_id: i64) -> String {
+    let router = Router::new().route(
+        "/send_private_msg",
+        post(move || async move {
+            Json(json!({
+                "status": "ok",
+                "retcode": 0,
+                "data": { "message_id": message_id },
+                "message": "",
+                "wording": "",
+            }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn http_actions_are_routed_by_account() {
+    let default_url = spawn_account_stand_in(1).await;
+    let account_url = spawn_account_stand_in(2).await;
+    let ws_hub = SignalHub::new();
+    let state_hub = SignalHub::new();
+    let act = ActionManager::with_http_client(
+        ws_hub.get_port(),
+        state_hub.get_port(),
+        Some(HttpActionClient::new(default_url, "").with_account(10002, account_url)),
+    );
+    let message = || Message::new().with_text("hello");
+
+    let res = act.send_private_message(42, message()).await.unwrap();
+    assert_eq!(res.message_id, 1);
+    let res = act
+        .for_account(10002)
+        .send_private_message(42, message())
+        .await
+        .unwrap();
+    assert_eq!(res.message_id, 2);
+    // 登记了账号地址后, 未登记的账号不会被发往默认地址
+    let err = act
+        .for_account(10003)
+        .send_private_message(42, message())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ActionError::Transport("no http endpoint for account 10003".into())
+    );
+}
+
+#[tokio::test]
+async fn http_actions_are_recorded() {
+    let base_url = spawn_napcat_stand_in().await;
+    let path = std::env::temp_dir().join(format!("meril-http-record-{}.jsonl", std::process::id()));
+    let recording = RecordingAdapter::new(MockAdapter::new(), &path).unwrap();
+    let act = http_action_manager(&base_url, "secret");
+    act.set_recorder(recording.recorder());
+    recording.clone().run();
+
+    act.send_private_message(10001, Message::new().with_text("hello"))
+        .await
+        .unwrap();
+    let mut entries = Vec::new();
+    for _ in 0..20 {
+        entries = read_recording(&path).unwrap();
+        if entries.len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    let kinds: Vec<RecordKind> = entries.iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, [RecordKind::Action, RecordKind::Response]);
+    assert_eq!(entries[0].data["action"], "send_private_msg");
+    assert_eq!(entries[1].data["data"]["message_id"], 42);
+    // 回放按 echo 将响应对应到动作
+    assert_eq!(entries[0].data["echo"], entries[1].data["echo"]);
+}
@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use meril_cat::{
+    config::RateLimit,
+    core::{
+        event::EventNexus,
+        limiter::{Conversation, RateLimiter},
+    },
+    prelude::{
+        ActionManager, ActionMiddleware, BasePlugin, Flow, MerilBot, Message, NapcatRequestData,
+        PluginWrapper,
+    },
+    testing::{MockAdapter, MockResponse, TestHarness},
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[tokio::test]
+async fn token_bucket_limits_bursts() {
+    let limiter = RateLimiter::new(
+        RateLimit::UNLIMITED,
+        RateLimit::new(20.0, 2),
+        RateLimit::UNLIMITED,
+    );
+    let start = Instant::now();
+    for _ in 0..4 {
+        drop(limiter.acquire(None, Conversation::Private(1), 0).await);
+    }
+    // 前 2 条立即发出, 之后每 50ms 一条
+    assert!(start.elapsed() >= Duration::from_millis(90));
+
+    // 其他会话不受影响
+    let start = Instant::now();
+    drop(limiter.acquire(None, Conversation::Private(2), 0).await);
+    assert!(start.elapsed() < Duration::from_millis(20));
+}
+
+#[tokio::test]
+async fn global_bucket_is_per_account() {
+    let limiter = RateLimiter::new(
+        RateLimit::new(20.0, 1),
+        RateLimit::UNLIMITED,
+        RateLimit::UNLIMITED,
+    );
+    drop(limiter.acquire(Some(1), Conversation::Group(1), 0).await);
+    let start = Instant::now();
+    drop(limiter.acquire(Some(2), Conversation::Group(2), 0).await);
+    assert!(start.elapsed() < Duration::from_millis(20));
+    drop(limiter.acquire(Some(1), Conversation::Group(2), 0).await);
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn conversation_queue_is_fifo_and_measured() {
+    let limiter = Arc::new(RateLimiter::unlimited());
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let first = limiter.acquire(None, Conversation::Group(7), 0).await;
+    let mut tasks = Vec::new();
+    for index in 0..5 {
+        let limiter = limiter.clone();
+        let order = order.clone();
+        tasks.push(tokio::spawn(async move {
+            let _permit = limiter.acquire(None, Conversation::Group(7), 0).await;
+            order.lock().unwrap().push(index);
+        }));
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(limiter.queue_depth(None, Conversation::Group(7)), 6);
+    assert_eq!(limiter.total_queue_depth(), 6);
+    drop(first);
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(limiter.queue_depth(None, Conversation::Group(7)), 0);
+    assert!(limiter.queue_metrics().is_empty());
+}
+
+#[tokio::test]
+async fn typing_delay_scales_with_length() {
+    let limiter = RateLimiter::unlimited()
+        .with_typing_delay(Duration::from_millis(10), Duration::from_millis(50));
+    let start = Instant::now();
+    drop(limiter.acquire(None, Conversation::Private(1), 3).await);
+    assert!(start.elapsed() >= Duration::from_millis(30));
+    let start = Instant::now();
+    drop(limiter.acquire(None, Conversation::Private(1), 100).await);
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(50) && elapsed < Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn cancelled_waiter_leaves_queue() {
+    let limiter = RateLimiter::unlimited();
+    let first = limiter.acquire(None, Conversation::Private(1), 0).await;
+    let waiting = tokio::time::timeout(
+        Duration::from_millis(20),
+        limiter.acquire(None, Conversation::Private(1), 0),
+    )
+    .await;
+    assert!(waiting.is_err());
+    assert_eq!(limiter.queue_depth(None, Conversation::Private(1)), 1);
+    drop(first);
+    assert_eq!(limiter.queue_depth(None, Conversation::Private(1)), 0);
+}
+
+#[tokio::test]
+async fn idle_queues_and_full_buckets_are_pruned() {
+    let limiter = RateLimiter::new(
+        RateLimit::new(1000.0, 1),
+        RateLimit::new(1000.0, 1),
+        RateLimit::UNLIMITED,
+    );
+    let busy = limiter.acquire(Some(1), Conversation::Private(1), 0).await;
+    for user_id in 2..10 {
+        drop(
+            limiter
+                .acquire(Some(1), Conversation::Private(user_id), 0)
+                .await,
+        );
+    }
+    // 不限速的会话不创建令牌桶
+    drop(limiter.acquire(Some(1), Conversation::Group(1), 0).await);
+    assert_eq!(limiter.tracked(), (10, 10));
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    limiter.prune();
+    // 仍在发送的会话保留队列; 令牌均已补满
+    assert_eq!(limiter.tracked(), (1, 0));
+    drop(busy);
+    limiter.prune();
+    assert_eq!(limiter.tracked(), (0, 0));
+}
+
+/// 禁止向某个群发送
+struct MuteGroup(i64);
+
+#[async_trait]
+impl ActionMiddleware for MuteGroup {
+    async fn before(&self, data: NapcatRequestData) -> Flow {
+        if data.params()["group_id"] == self.0 {
+            return Flow::reject("muted");
+        }
+        Flow::Continue(data)
+    }
+}
+
+#[tokio::test]
+async fn blocked_messages_do_not_take_tokens() {
+    let harness = TestHarness::new();
+    let act = harness.action.with_rate_limiter(RateLimiter::new(
+        RateLimit::new(0.1, 1),
+        RateLimit::UNLIMITED,
+        RateLimit::UNLIMITED,
+    ));
+    act.add_middleware(MuteGroup(7));
+    for _ in 0..3 {
+        let res = act
+            .send_group_message(7, Message::new().with_text("hi"))
+            .await;
+        assert!(res.is_err());
+    }
+    let start = Instant::now();
+    act.send_group_message(8, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn permit_is_released_once_sent() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("send_group_msg", MockResponse::NoReply);
+    let act = harness.action.with_timeout(Duration::from_secs(30));
+    let first = act.clone();
+    let pending = tokio::spawn(async move {
+        first
+            .send_group_message(7, Message::new().with_text("1"))
+            .await
+    });
+    harness
+        .adapter
+        .wait_for_action("send_group_msg", 0, Duration::from_secs(1))
+        .await
+        .unwrap();
+    // 第一条等待响应时, 同一会话的第二条照常发出
+    act.send_group_message(7, Message::new().with_text("2"))
+        .await
+        .unwrap();
+    let second = harness
+        .adapter
+        .wait_for_action("send_group_msg", 1, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(second.params()["message"][0]["data"]["text"], "2");
+    assert!(!pending.is_finished());
+    pending.abort();
+}
+
+/// 启动后向同一个群连发两条消息
+struct Burst;
+
+#[async_trait]
+impl BasePlugin for Burst {
+    async fn on_load(self: Arc<Self>) {}
+    async fn on_update(self: Arc<Self>, _event_nexus: Arc<EventNexus>, act: Arc<ActionManager>) {
+        for text in ["1", "2"] {
+            let _ = act
+                .send_group_message(7, Message::new().with_text(text))
+                .await;
+        }
+        std::future::pending::<()>().await;
+    }
+    async fn on_unload(self: Arc<Self>) {}
+}
+
+#[tokio::test]
+async fn bot_rate_limiter_applies_to_plugins() {
+    let adapter = MockAdapter::new();
+    let bot = MerilBot::with_adapter(adapter.clone()).with_rate_limiter(RateLimiter::new(
+        RateLimit::UNLIMITED,
+        RateLimit::UNLIMITED,
+        RateLimit::new(10.0, 1),
+    ));
+    bot.plugin
+        .clone()
+        .add_plugin(PluginWrapper::new(Burst))
+        .await;
+    let action = bot.action.clone();
+    let start = Instant::now();
+    tokio::spawn(async move { bot.run().await });
+
+    adapter
+        .wait_for_action("send_group_msg", 1, Duration::from_secs(2))
+        .await
+        .unwrap();
+    // 突发 1 条, 之后每 100ms 一条
+    assert!(start.elapsed() >= Duration::from_millis(90));
+    assert_eq!(action.rate_limiter().tracked().1, 1);
+}
@@ -0,0 +1,154 @@
+use meril_cat::{
+    prelude::{ForwardMessage, MediaSource, Message},
+    testing::{MockResponse, TestHarness},
+};
+use serde_json::json;
+use std::{path::PathBuf, time::Duration};
+
+const WAIT: Duration = Duration::from_secs(1);
+
+#[test]
+fn media_sources_are_encoded() {
+    assert_eq!(MediaSource::bytes(b"hi".to_vec()).encode(), "base64://aGk=");
+    assert_eq!(
+        MediaSource::from("https://example.com/a.png").encode(),
+        "https://example.com/a.png"
+    );
+    assert_eq!(MediaSource::from("base64://aGk=").encode(), "base64://aGk=");
+
+    let encoded = MediaSource::path(PathBuf::from("images/cat.png")).encode();
+    assert!(encoded.starts_with("file:///"));
+    assert!(encoded.ends_with("images/cat.png"));
+
+    let message = serde_json::to_value(Message::new().with_image(vec![1u8, 2, 3])).unwrap();
+    assert_eq!(message[0]["data"]["file"], "base64://AQID");
+}
+
+#[test]
+fn file_names_are_derived() {
+    assert_eq!(
+        MediaSource::path("/tmp/report.pdf").file_name().as_deref(),
+        Some("report.pdf")
+    );
+    assert_eq!(
+        MediaSource::url("https://example.com/x/song.mp3?sig=1")
+            .file_name()
+            .as_deref(),
+        Some("song.mp3")
+    );
+    assert_eq!(MediaSource::bytes(vec![0u8]).file_name(), None);
+}
+
+#[tokio::test]
+async fn upload_group_file_from_memory() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "upload_group_file",
+        MockResponse::Ok(json!({ "file_id": "/abc" })),
+    );
+    let file_id = harness
+        .action
+        .upload_group_file(100, b"report".to_vec(), "report.txt", None)
+        .await
+        .unwrap();
+    assert_eq!(file_id.as_deref(), Some("/abc"));
+    let sent = harness
+        .adapter
+        .wait_for_action("upload_group_file", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(sent.params()["file"], "base64://cmVwb3J0");
+    assert_eq!(sent.params()["name"], "report.txt");
+
+    // 不返回数据的实现
+    harness
+        .action
+        .upload_private_file(42, MediaSource::path("/tmp/a.txt"), "")
+        .await
+        .unwrap();
+    let sent = harness
+        .adapter
+        .wait_for_action("upload_private_file", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(sent.params()["name"], "a.txt");
+    assert_eq!(sent.params()["file"], "file:///tmp/a.txt");
+}
+
+#[tokio::test]
+async fn group_file_listing() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "get_group_root_files",
+        MockResponse::Ok(json!({
+            "files": [{ "file_id": "/a", "file_name": "a.txt", "busid": 102, "file_size": 3 }],
+            "folders": null,
+        })),
+    );
+    harness.adapter.respond(
+        "get_group_file_url",
+        MockResponse::Ok(json!({ "url": "https://example.com/a.txt" })),
+    );
+    let files = harness.action.get_group_root_files(100).await.unwrap();
+    assert_eq!(files.files[0].file_name, "a.txt");
+    assert!(files.folders.is_empty());
+    let url = harness
+        .action
+        .get_group_file_url(100, &files.files[0].file_id, files.files[0].busid)
+        .await
+        .unwrap();
+    assert_eq!(url, "https://example.com/a.txt");
+
+    harness
+        .action
+        .create_group_file_folder(100, "logs", None)
+        .await
+        .unwrap();
+    let sent = harness
+        .adapter
+        .wait_for_action("create_group_file_folder", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(sent.params()["folder_name"], "logs");
+}
+
+#[tokio::test]
+async fn local_media_is_inlined_when_enabled() {
+    let path = std::env::temp_dir().join(format!("meril_inline_{}.png", std::process::id()));
+    std::fs::write(&path, b"hi").unwrap();
+    let source = MediaSource::path(&path);
+    assert_eq!(source.encode_with(true).await, "base64://aGk=");
+    assert!(source.encode_with(false).await.starts_with("file:///"));
+    // 读取失败时退回 file:// 路径
+    let missing = MediaSource::path("/nonexistent/meril.png");
+    assert_eq!(missing.encode_with(true).await, missing.encode());
+
+    let harness = TestHarness::new();
+    let act = harness.action.with_inline_local_media(true);
+    act.send_private_message(42, Message::new().with_image(path.clone()))
+        .await
+        .unwrap();
+    let forward = ForwardMessage::new().with_node(1, "a", Message::new().with_record(path.clone()));
+    act.send_group_forward_msg(100, forward).await.unwrap();
+    act.upload_private_file(42, path.clone(), "").await.unwrap();
+    harness
+        .action
+        .send_private_message(42, Message::new().with_image(path.clone()))
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let sent = harness.adapter.sent_actions().await;
+    assert_eq!(
+        sent[0].params()["message"][0]["data"]["file"],
+        "base64://aGk="
+    );
+    let node = &sent[1].params()["messages"][0]["data"];
+    assert_eq!(node["content"][0]["data"]["file"], "base64://aGk=");
+    assert_eq!(sent[2].params()["file"], "base64://aGk=");
+    // 未开启时仍发送路径
+    let file = sent[3].params()["message"][0]["data"]["file"]
+        .as_str()
+        .unwrap();
+    assert!(file.starts_with("file:///"));
+}
@@ -0,0 +1,176 @@
+use async_trait::async_trait;
+use meril_cat::{
+    prelude::{ActionError, ActionMiddleware, Adapter, Flow, MerilBot, Message, NapcatRequestData},
+    testing::{MockAdapter, TestHarness},
+};
+use serde_json::{Value, json};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// 替换消息中的敏感词
+struct Censor;
+
+#[async_trait]
+impl ActionMiddleware for Censor {
+    async fn before(&self, mut data: NapcatRequestData) -> Flow {
+        if let Some(segments) = data.params_mut()["message"].as_array_mut() {
+            for segment in segments {
+                if let Some(text) = segment["data"]["text"].as_str() {
+                    segment["data"]["text"] = json!(text.replace("bad", "***"));
+                }
+            }
+        }
+        Flow::Continue(data)
+    }
+}
+
+/// 禁止向某个群发送
+struct MuteGroup(i64);
+
+#[async_trait]
+impl ActionMiddleware for MuteGroup {
+    async fn before(&self, data: NapcatRequestData) -> Flow {
+        if data.params()["group_id"] == self.0 {
+            return Flow::reject("muted");
+        }
+        Flow::Continue(data)
+    }
+}
+
+/// 记录经过的动作与结果
+#[derive(Clone, Default)]
+struct Audit(Arc<Mutex<Vec<String>>>);
+
+#[async_trait]
+impl ActionMiddleware for Audit {
+    async fn before(&self, data: NapcatRequestData) -> Flow {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("before {}", data.action()));
+        Flow::Continue(data)
+    }
+
+    async fn after(&self, data: &NapcatRequestData, res: &Result<Value, ActionError>) {
+        let result = if res.is_ok() { "ok" } else { "err" };
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("after {} {}", data.action(), result));
+    }
+}
+
+struct Delay(Duration);
+
+#[async_trait]
+impl ActionMiddleware for Delay {
+    async fn before(&self, data: NapcatRequestData) -> Flow {
+        tokio::time::sleep(self.0).await;
+        Flow::Continue(data)
+    }
+}
+
+#[tokio::test]
+async fn middleware_rewrites_request() {
+    let harness = TestHarness::new();
+    harness.action.add_middleware(Censor);
+    harness
+        .action
+        .send_private_message(42, Message::new().with_text("a bad word"))
+        .await
+        .unwrap();
+
+    let sent = harness.adapter.sent_actions().await;
+    assert_eq!(sent[0].params()["message"][0]["data"]["text"], "a *** word");
+}
+
+#[tokio::test]
+async fn blocked_request_never_reaches_adapter() {
+    let harness = TestHarness::new();
+    let audit = Audit::default();
+    harness.action.add_middleware(audit.clone());
+    harness.action.add_middleware(MuteGroup(100));
+
+    let res = harness
+        .action
+        .send_group_message(100, Message::new().with_text("hi"))
+        .await;
+    assert_eq!(res.unwrap_err(), ActionError::Blocked("muted".into()));
+    harness
+        .action
+        .send_group_message(200, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+
+    let sent = harness.adapter.sent_actions().await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].params()["group_id"], 200);
+    assert_eq!(
+        *audit.0.lock().unwrap(),
+        [
+            "before send_group_msg",
+            "after send_group_msg err",
+            "before send_group_msg",
+            "after send_group_msg ok",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn middleware_can_fake_response() {
+    struct Fake;
+
+    #[async_trait]
+    impl ActionMiddleware for Fake {
+        async fn before(&self, _data: NapcatRequestData) -> Flow {
+            Flow::respond(json!({ "message_id": 99 }))
+        }
+    }
+
+    let harness = TestHarness::new();
+    harness.action.add_middleware(Fake);
+    let sent = harness
+        .action
+        .send_private_message(42, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+    assert_eq!(sent.message_id, 99);
+    assert!(harness.adapter.sent_actions().await.is_empty());
+}
+
+#[tokio::test]
+async fn middleware_can_delay() {
+    let harness = TestHarness::new();
+    harness
+        .action
+        .add_middleware(Delay(Duration::from_millis(200)));
+    let start = Instant::now();
+    harness.action.send_like(42, 1).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn middleware_applies_to_scoped_copies() {
+    let harness = TestHarness::new();
+    let audit = Audit::default();
+    let act = harness.action.for_account(1);
+    harness.action.add_middleware(audit.clone());
+    act.send_like(42, 1).await.unwrap();
+    assert_eq!(audit.0.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn bot_registers_middleware() {
+    let adapter = MockAdapter::new();
+    let audit = Audit::default();
+    let bot = MerilBot::with_adapter(adapter.clone()).with_middleware(audit.clone());
+    adapter.clone().run();
+    bot.action.clone().run();
+    bot.action.send_like(42, 1).await.unwrap();
+    assert_eq!(
+        *audit.0.lock().unwrap(),
+        ["before send_like", "after send_like ok"]
+    );
+}
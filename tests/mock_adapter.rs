@@ -0,0 +1,95 @@
+use meril_cat::{
+    prelude::Message,
+    testing::{MockResponse, TEST_SELF_ID, TestHarness},
+    types::action_type::ActionError,
+};
+use serde_json::json;
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(1);
+
+#[tokio::test]
+async fn captures_actions_and_returns_scripted_response() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "send_private_msg",
+        MockResponse::Ok(json!({ "message_id": 7 })),
+    );
+    let res = harness
+        .action
+        .send_private_message(42, Message::new().with_text("hi"))
+        .await
+        .unwrap();
+    assert_eq!(res.message_id, 7);
+
+    let sent = harness
+        .adapter
+        .wait_for_action("send_private_msg", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(sent.params()["user_id"], 42);
+    assert_eq!(sent.params()["message"][0]["data"]["text"], "hi");
+}
+
+#[tokio::test]
+async fn scripted_failure_reaches_caller() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "send_group_msg",
+        MockResponse::Failed {
+            retcode: 1200,
+            message: "bot is muted".into(),
+        },
+    );
+    let err = harness
+        .action
+        .send_group_message(1, Message::new().with_text("hi"))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ActionError::Failed {
+            retcode: 1200,
+            message: "bot is muted".into()
+        }
+    );
+    assert_eq!(err.retcode(), Some(1200));
+}
+
+#[tokio::test]
+async fn disconnect_fails_unanswered_requests() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("send_private_msg", MockResponse::NoReply);
+    let act = harness.action.for_account(TEST_SELF_ID);
+    let request = tokio::spawn(async move {
+        act.send_private_message(42, Message::new().with_text("hi"))
+            .await
+    });
+    harness
+        .adapter
+        .wait_for_action("send_private_msg", 0, WAIT)
+        .await
+        .unwrap();
+    harness.adapter.disconnect(TEST_SELF_ID, "test");
+    let res = tokio::time::timeout(WAIT, request).await.unwrap().unwrap();
+    assert_eq!(
+        res.unwrap_err(),
+        ActionError::TransportClosed("Connection Lost".into())
+    );
+}
+
+#[tokio::test]
+async fn events_reach_event_nexus() {
+    let harness = TestHarness::new();
+    let port = harness.event_nexus.get_group_message_port();
+    harness.adapter.push_group_message(100, 42, "hello");
+    let event = tokio::time::timeout(WAIT, port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.group_id, 100);
+    assert_eq!(event.sender.user_id, 42);
+    assert_eq!(event.raw_message, "hello");
+}
@@ -0,0 +1,170 @@
+use meril_cat::{
+    core::adapter::{NapcatAdapter, NapcatOptions},
+    prelude::{ActionManager, Adapter},
+    testing::NapcatPeer,
+    types::{
+        action_type::ActionError,
+        event_type::connection_event::{ConnectionEvent, ConnectionState},
+        signal_type::SignalPort,
+    },
+};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+
+const WAIT: Duration = Duration::from_secs(1);
+const ACCOUNT_A: i64 = 10001;
+const ACCOUNT_B: i64 = 10002;
+
+fn setup(options: NapcatOptions) -> (Arc<NapcatAdapter>, Arc<ActionManager>) {
+    let adapter = NapcatAdapter::with_options(options);
+    adapter.clone().run_detached();
+    let act =
+        ActionManager::with_http_client(adapter.get_action_port(), adapter.get_state_port(), None);
+    act.clone().run();
+    (adapter, act)
+}
+
+async fn next_state(port: &SignalPort<ConnectionEvent>) -> ConnectionEvent {
+    tokio::time::timeout(WAIT, port.recv())
+        .await
+        .expect("state")
+        .unwrap()
+}
+
+async fn no_state(port: &SignalPort<ConnectionEvent>) -> bool {
+    tokio::time::timeout(Duration::from_millis(100), port.recv())
+        .await
+        .is_err()
+}
+
+/// 接入并绑定到 self_id 的模拟 Napcat
+async fn peer(
+    adapter: &Arc<NapcatAdapter>,
+    states: &SignalPort<ConnectionEvent>,
+    self_id: i64,
+) -> NapcatPeer {
+    let mut peer = NapcatPeer::connect(adapter).await;
+    peer.hello(self_id).await;
+    let state = next_state(states).await;
+    assert_eq!(state.state, ConnectionState::Connected);
+    assert_eq!(state.self_id, self_id);
+    peer
+}
+
+#[tokio::test]
+async fn actions_are_routed_by_account() {
+    let (adapter, act) = setup(NapcatOptions::default());
+    let states = adapter.get_state_port();
+    let mut peer_a = peer(&adapter, &states, ACCOUNT_A).await;
+    let mut peer_b = peer(&adapter, &states, ACCOUNT_B).await;
+
+    let a = act.for_account(ACCOUNT_A);
+    let request = tokio::spawn(async move { a.send_like(1, 1).await });
+    let action = peer_a.answer(Value::Null, WAIT).await.unwrap();
+    assert_eq!(action["action"], "send_like");
+    // 账号只用于路由, 不会发给 Napcat
+    assert!(action.get("self_id").is_none());
+    request.await.unwrap().unwrap();
+
+    let b = act.for_account(ACCOUNT_B);
+    let request = tokio::spawn(async move { b.send_like(2, 1).await });
+    let action = peer_b.answer(Value::Null, WAIT).await.unwrap();
+    assert_eq!(action["params"]["user_id"], 2);
+    request.await.unwrap().unwrap();
+
+    let err = act.for_account(10003).send_like(3, 1).await.unwrap_err();
+    assert!(matches!(err, ActionError::Failed { retcode: 1404, .. }));
+}
+
+#[tokio::test]
+async fn unscoped_action_with_several_accounts_is_ambiguous() {
+    let (adapter, act) = setup(NapcatOptions::default());
+    let states = adapter.get_state_port();
+    let mut peer_a = peer(&adapter, &states, ACCOUNT_A).await;
+
+    // 只有一个账号时交给它
+    let unscoped = act.clone();
+    let request = tokio::spawn(async move { unscoped.send_like(1, 1).await });
+    peer_a.answer(Value::Null, WAIT).await.unwrap();
+    request.await.unwrap().unwrap();
+
+    let _peer_b = peer(&adapter, &states, ACCOUNT_B).await;
+    let err = act.send_like(1, 1).await.unwrap_err();
+    assert_eq!(
+        err,
+        ActionError::Failed {
+            retcode: 1400,
+            message: "ambiguous account".into()
+        }
+    );
+}
+
+#[tokio::test]
+async fn unscoped_action_uses_default_account() {
+    let (adapter, act) = setup(NapcatOptions {
+        default_account: Some(ACCOUNT_B),
+        ..Default::default()
+    });
+    let states = adapter.get_state_port();
+    let _peer_a = peer(&adapter, &states, ACCOUNT_A).await;
+    let mut peer_b = peer(&adapter, &states, ACCOUNT_B).await;
+
+    let unscoped = act.clone();
+    let request = tokio::spawn(async move { unscoped.send_like(1, 1).await });
+    let action = peer_b.answer(Value::Null, WAIT).await.unwrap();
+    assert_eq!(action["action"], "send_like");
+    request.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn sessions_bind_from_first_frame_without_clobbering() {
+    // 即使配置了默认账号, 连接也不会预先绑定到它
+    let (adapter, _act) = setup(NapcatOptions {
+        default_account: Some(ACCOUNT_A),
+        ..Default::default()
+    });
+    let states = adapter.get_state_port();
+    let mut peer_a = NapcatPeer::connect(&adapter).await;
+    let mut peer_b = NapcatPeer::connect(&adapter).await;
+    assert!(no_state(&states).await);
+
+    peer_a.hello(ACCOUNT_A).await;
+    peer_b.hello(ACCOUNT_B).await;
+    let mut connected = vec![];
+    for _ in 0..2 {
+        let state = next_state(&states).await;
+        assert_eq!(state.state, ConnectionState::Connected);
+        connected.push(state.self_id);
+    }
+    connected.sort();
+    assert_eq!(connected, [ACCOUNT_A, ACCOUNT_B]);
+    // 同一账号的后续数据不会再次绑定
+    peer_a.hello(ACCOUNT_A).await;
+    assert!(no_state(&states).await);
+}
+
+#[tokio::test]
+async fn session_rebinds_when_account_changes() {
+    let (adapter, act) = setup(NapcatOptions::default());
+    let states = adapter.get_state_port();
+    let mut peer = peer(&adapter, &states, ACCOUNT_A).await;
+
+    peer.hello(ACCOUNT_B).await;
+    let old = next_state(&states).await;
+    assert_eq!(old.state, ConnectionState::Disconnected);
+    assert_eq!(old.self_id, ACCOUNT_A);
+    assert_eq!(old.reason, "account changed");
+    let new = next_state(&states).await;
+    assert_eq!(new.state, ConnectionState::Connected);
+    assert_eq!(new.self_id, ACCOUNT_B);
+
+    let b = act.for_account(ACCOUNT_B);
+    let request = tokio::spawn(async move { b.send_like(1, 1).await });
+    peer.answer(Value::Null, WAIT).await.unwrap();
+    request.await.unwrap().unwrap();
+    let err = act.for_account(ACCOUNT_A).send_like(1, 1).await;
+    assert!(matches!(
+        err,
+        Err(ActionError::Failed { retcode: 1404, .. })
+    ));
+}
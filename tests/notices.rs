@@ -0,0 +1,194 @@
+use meril_cat::{
+    testing::{TEST_SELF_ID, TestHarness},
+    types::event_type::notice_event::{NoticeEvent, NotifyEvent},
+};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(1);
+
+fn notice(notice_type: &str, extra: Value) -> Value {
+    let mut event = json!({
+        "post_type": "notice",
+        "notice_type": notice_type,
+        "self_id": TEST_SELF_ID,
+        "time": 1700000000,
+    });
+    event
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    event
+}
+
+fn parse(event: Value) -> NoticeEvent {
+    serde_json::from_value(event).unwrap()
+}
+
+#[test]
+fn notices_parse_into_variants() {
+    let cases = [
+        notice(
+            "group_recall",
+            json!({ "group_id": 1, "user_id": 2, "operator_id": 3, "message_id": 4 }),
+        ),
+        notice("friend_recall", json!({ "user_id": 2, "message_id": 4 })),
+        notice(
+            "group_increase",
+            json!({ "sub_type": "invite", "group_id": 1, "operator_id": 3, "user_id": 2 }),
+        ),
+        notice(
+            "group_decrease",
+            json!({ "sub_type": "kick", "group_id": 1, "operator_id": 3, "user_id": 2 }),
+        ),
+        notice(
+            "group_admin",
+            json!({ "sub_type": "set", "group_id": 1, "user_id": 2 }),
+        ),
+        notice(
+            "group_ban",
+            json!({ "sub_type": "ban", "group_id": 1, "operator_id": 3, "user_id": 2, "duration": 600 }),
+        ),
+        notice(
+            "group_upload",
+            json!({ "group_id": 1, "user_id": 2, "file": { "id": "f", "name": "a.txt", "size": 3, "busid": 102 } }),
+        ),
+        notice("friend_add", json!({ "user_id": 2 })),
+        notice(
+            "group_card",
+            json!({ "group_id": 1, "user_id": 2, "card_new": "new", "card_old": "old" }),
+        ),
+        notice(
+            "essence",
+            json!({ "sub_type": "add", "group_id": 1, "message_id": 4, "sender_id": 2, "operator_id": 3 }),
+        ),
+        notice(
+            "group_msg_emoji_like",
+            json!({ "group_id": 1, "user_id": 2, "message_id": 4, "likes": [{ "emoji_id": "76", "count": 1 }] }),
+        ),
+    ];
+    let parsed: Vec<NoticeEvent> = cases.into_iter().map(parse).collect();
+    assert!(matches!(&parsed[0], NoticeEvent::GroupRecall(e) if e.operator_id == 3));
+    assert!(matches!(&parsed[1], NoticeEvent::FriendRecall(e) if e.message_id == 4));
+    assert!(matches!(&parsed[2], NoticeEvent::GroupIncrease(e) if e.sub_type == "invite"));
+    assert!(matches!(&parsed[3], NoticeEvent::GroupDecrease(e) if e.sub_type == "kick"));
+    assert!(matches!(&parsed[4], NoticeEvent::GroupAdmin(e) if e.sub_type == "set"));
+    assert!(matches!(&parsed[5], NoticeEvent::GroupBan(e) if e.duration == 600));
+    assert!(matches!(&parsed[6], NoticeEvent::GroupUpload(e) if e.file.name == "a.txt"));
+    assert!(matches!(&parsed[7], NoticeEvent::FriendAdd(e) if e.user_id == 2));
+    assert!(matches!(&parsed[8], NoticeEvent::GroupCard(e) if e.card_old == "old"));
+    assert!(matches!(&parsed[9], NoticeEvent::Essence(e) if e.sender_id == 2));
+    assert!(
+        matches!(&parsed[10], NoticeEvent::GroupMsgEmojiLike(e) if e.likes[0].emoji_id == "76")
+    );
+    for notice in &parsed {
+        assert_eq!(notice.self_id(), Some(TEST_SELF_ID));
+    }
+    assert_eq!(parsed[0].group_id(), Some(1));
+    assert_eq!(parsed[1].group_id(), None);
+}
+
+#[test]
+fn notify_sub_types_parse() {
+    let poke = parse(notice(
+        "notify",
+        json!({ "sub_type": "poke", "user_id": 2, "target_id": TEST_SELF_ID }),
+    ));
+    assert!(
+        matches!(&poke, NoticeEvent::Notify(NotifyEvent::Poke(e)) if e.group_id.is_none() && e.target_id == TEST_SELF_ID)
+    );
+    let lucky_king = parse(notice(
+        "notify",
+        json!({ "sub_type": "lucky_king", "group_id": 1, "user_id": 2, "target_id": 3 }),
+    ));
+    assert!(matches!(
+        lucky_king,
+        NoticeEvent::Notify(NotifyEvent::LuckyKing(_))
+    ));
+    let honor = parse(notice(
+        "notify",
+        json!({ "sub_type": "honor", "group_id": 1, "user_id": 2, "honor_type": "talkative" }),
+    ));
+    assert!(
+        matches!(&honor, NoticeEvent::Notify(NotifyEvent::Honor(e)) if e.honor_type == "talkative")
+    );
+    let title = parse(notice(
+        "notify",
+        json!({ "sub_type": "title", "group_id": 1, "user_id": 2, "title": "king" }),
+    ));
+    assert!(matches!(&title, NoticeEvent::Notify(NotifyEvent::Title(e)) if e.title == "king"));
+    let input = parse(notice(
+        "notify",
+        json!({ "sub_type": "input_status", "group_id": 0, "user_id": 2, "status_text": "对方正在输入...", "event_type": 1 }),
+    ));
+    assert!(matches!(
+        &input,
+        NoticeEvent::Notify(NotifyEvent::InputStatus(e)) if e.status_text == "对方正在输入..."
+    ));
+    assert_eq!(input.group_id(), None);
+    let unknown = parse(notice(
+        "notify",
+        json!({ "sub_type": "profile_like", "user_id": 2 }),
+    ));
+    assert!(matches!(unknown, NoticeEvent::Notify(NotifyEvent::Other)));
+    let unknown = parse(notice("bot_offline", json!({})));
+    assert!(matches!(unknown, NoticeEvent::Other));
+}
+
+#[tokio::test]
+async fn notices_reach_typed_ports() {
+    let harness = TestHarness::new();
+    let notice_port = harness.event_nexus.get_notice_port();
+    let recall_port = harness.event_nexus.get_group_recall_port();
+    let poke_port = harness.event_nexus.get_poke_port();
+    let increase_port = harness.event_nexus.get_group_increase_port();
+
+    harness.adapter.push_event(notice(
+        "group_recall",
+        json!({ "group_id": 1, "user_id": 2, "operator_id": 2, "message_id": 4 }),
+    ));
+    harness.adapter.push_event(notice(
+        "notify",
+        json!({ "sub_type": "poke", "group_id": 1, "user_id": 2, "target_id": TEST_SELF_ID }),
+    ));
+
+    let recall = tokio::time::timeout(WAIT, recall_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(recall.message_id, 4);
+    let poke = tokio::time::timeout(WAIT, poke_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(poke.group_id, Some(1));
+    for _ in 0..2 {
+        tokio::time::timeout(WAIT, notice_port.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    // 其他类型的端口不会收到
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), increase_port.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn group_name_changes_reach_their_port() {
+    let harness = TestHarness::new();
+    let group_name_port = harness.event_nexus.get_group_name_port();
+    harness.adapter.push_event(notice(
+        "notify",
+        json!({ "sub_type": "group_name", "group_id": 1, "user_id": 2, "name_new": "新群名" }),
+    ));
+
+    let renamed = tokio::time::timeout(WAIT, group_name_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(renamed.group_id, 1);
+    assert_eq!(renamed.name_new, "新群名");
+}
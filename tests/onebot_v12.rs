@@ -0,0 +1,293 @@
+use meril_cat::{
+    core::onebot_v12::OneBotV12Adapter,
+    prelude::{ActionManager, Adapter, Message, NapcatRequestData},
+    testing::{MockAdapter, MockResponse},
+    types::{event_type::AnyEvent, message_type::MessageSegment},
+};
+use serde_json::{Value, json};
+use std::{sync::Arc, time::Duration};
+
+const WAIT: Duration = Duration::from_secs(1);
+
+fn setup(capacity: usize) -> (Arc<MockAdapter>, Arc<OneBotV12Adapter>, Arc<ActionManager>) {
+    let mock = MockAdapter::new();
+    let adapter = OneBotV12Adapter::with_id_capacity(mock.clone(), capacity);
+    adapter.clone().run();
+    let act =
+        ActionManager::with_http_client(adapter.get_action_port(), adapter.get_state_port(), None);
+    act.clone().run();
+    (mock, adapter, act)
+}
+
+fn group_message(message_id: &str, message: Value) -> Value {
+    json!({
+        "id": "event-1",
+        "time": 1700000000.5,
+        "type": "message",
+        "detail_type": "group",
+        "sub_type": "",
+        "self": { "platform": "qq", "user_id": "10001" },
+        "message_id": message_id,
+        "group_id": "20001",
+        "user_id": "42",
+        "alt_message": "hi",
+        "message": message,
+    })
+}
+
+#[tokio::test]
+async fn message_event_is_translated_to_v11() {
+    let (mock, adapter, _act) = setup(16);
+    let events = adapter.get_event_port();
+    mock.push_event(group_message(
+        "abc",
+        json!([
+            { "type": "text", "data": { "text": "hi" } },
+            { "type": "mention", "data": { "user_id": "42" } },
+            { "type": "location", "data": { "latitude": 0.0, "longitude": 0.0 } },
+            { "type": "qq.face", "data": { "id": "1" } },
+        ]),
+    ));
+    let value = tokio::time::timeout(WAIT, events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value["self_id"], 10001);
+    assert_eq!(value["group_id"], 20001);
+    assert_eq!(value["time"], 1700000000);
+
+    // 不支持的消息段被丢弃, 整条消息仍能解析
+    let AnyEvent::Message(event) = serde_json::from_value::<AnyEvent>(value).unwrap() else {
+        panic!("not a message event");
+    };
+    let segments = event.segments();
+    assert_eq!(segments.len(), 2);
+    assert!(matches!(&segments[0], MessageSegment::Text { text } if text == "hi"));
+    assert_eq!(segments[1].at_id(), Some(42));
+}
+
+#[test]
+fn at_segments_accept_numeric_and_all() {
+    let at = MessageSegment::at(42);
+    assert_eq!(serde_json::to_value(&at).unwrap()["data"]["qq"], "42");
+    assert_eq!(at.at_id(), Some(42));
+    let parsed: MessageSegment =
+        serde_json::from_value(json!({ "type": "at", "data": { "qq": 42 } })).unwrap();
+    assert_eq!(parsed.at_id(), Some(42));
+    assert_eq!(MessageSegment::at_all().at_id(), None);
+}
+
+#[tokio::test]
+async fn sent_media_is_uploaded_and_message_id_round_trips() {
+    let (mock, _adapter, act) = setup(16);
+    mock.respond(
+        "upload_file",
+        MockResponse::Ok(json!({ "file_id": "file-1" })),
+    );
+    mock.respond(
+        "send_message",
+        MockResponse::Ok(json!({ "message_id": "abc" })),
+    );
+    let message = Message::new()
+        .with_text("hi")
+        .with_at(42)
+        .with_image("https://example.com/a.png");
+    let sent = act.send_group_message(20001, message).await.unwrap();
+
+    let upload = mock.wait_for_action("upload_file", 0, WAIT).await.unwrap();
+    assert_eq!(upload.params()["type"], "url");
+    assert_eq!(upload.params()["url"], "https://example.com/a.png");
+    let send = mock.wait_for_action("send_message", 0, WAIT).await.unwrap();
+    assert_eq!(send.params()["detail_type"], "group");
+    assert_eq!(send.params()["group_id"], "20001");
+    assert_eq!(
+        send.params()["message"],
+        json!([
+            { "type": "text", "data": { "text": "hi" } },
+            { "type": "mention", "data": { "user_id": "42" } },
+            { "type": "image", "data": { "file_id": "file-1" } },
+        ])
+    );
+
+    // v12 的字符串 message_id 在撤回时还原
+    sent.recall(&act).await.unwrap();
+    let delete = mock
+        .wait_for_action("delete_message", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(delete.params()["message_id"], "abc");
+}
+
+#[tokio::test]
+async fn response_fields_are_translated() {
+    let (mock, _adapter, act) = setup(16);
+    mock.respond(
+        "get_self_info",
+        MockResponse::Ok(json!({ "user_id": "10001", "user_name": "meril" })),
+    );
+    let info = act.get_login_info().await.unwrap();
+    assert_eq!(info.user_id, 10001);
+    assert_eq!(info.nickname, "meril");
+}
+
+#[tokio::test]
+async fn pending_upload_does_not_block_other_actions() {
+    let (mock, _adapter, act) = setup(16);
+    mock.respond("upload_file", MockResponse::NoReply);
+    let sender = act.clone();
+    let _upload = tokio::spawn(async move {
+        let message = Message::new().with_image("https://example.com/a.png");
+        sender.send_group_message(20001, message).await
+    });
+    mock.wait_for_action("upload_file", 0, WAIT).await.unwrap();
+
+    tokio::time::timeout(WAIT, act.send_like(42, 1))
+        .await
+        .expect("blocked by upload")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn message_ids_are_bounded() {
+    let (mock, adapter, act) = setup(2);
+    let events = adapter.get_event_port();
+    for id in ["a", "b", "c"] {
+        mock.push_event(group_message(id, json!([])));
+        tokio::time::timeout(WAIT, events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    assert_eq!(adapter.tracked_message_ids(), 2);
+
+    // 仍记得的 ID 正常还原
+    mock.push_event(group_message("c", json!([])));
+    let value = tokio::time::timeout(WAIT, events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let message_id = value["message_id"].as_i64().unwrap();
+    act.delete_msg(message_id).await.unwrap();
+    let delete = mock
+        .wait_for_action("delete_message", 0, WAIT)
+        .await
+        .unwrap();
+    assert_eq!(delete.params()["message_id"], "c");
+    assert_eq!(adapter.tracked_message_ids(), 2);
+}
+
+#[tokio::test]
+async fn messages_to_one_chat_keep_their_order() {
+    let (mock, adapter, act) = setup(16);
+    mock.respond(
+        "upload_file",
+        MockResponse::Delayed(
+            Duration::from_millis(200),
+            Box::new(MockResponse::Ok(json!({ "file_id": "file-1" }))),
+        ),
+    );
+    for id in ["m1", "m2", "m3"] {
+        mock.respond(
+            "send_message",
+            MockResponse::Ok(json!({ "message_id": id })),
+        );
+    }
+    let sender = act.clone();
+    let with_image = tokio::spawn(async move {
+        let message = Message::new().with_image("https://example.com/a.png");
+        sender.send_group_message(20001, message).await
+    });
+    mock.wait_for_action("upload_file", 0, WAIT).await.unwrap();
+
+    // 其他会话不必等待上传
+    let start = tokio::time::Instant::now();
+    act.send_group_message(20002, Message::new().with_text("other"))
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_millis(150));
+
+    // 同一会话的后一条消息不会越过正在上传的前一条
+    act.send_group_message(20001, Message::new().with_text("after"))
+        .await
+        .unwrap();
+    with_image.await.unwrap().unwrap();
+    let sent: Vec<Value> = mock
+        .sent_actions()
+        .await
+        .into_iter()
+        .filter(|data| data.action() == "send_message" && data.params()["group_id"] == "20001")
+        .map(|data| data.params()["message"][0]["type"].clone())
+        .collect();
+    assert_eq!(sent, [json!("image"), json!("text")]);
+    // 转发完成后不再保留会话
+    for _ in 0..20 {
+        if adapter.busy_lanes() == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(adapter.busy_lanes(), 0);
+}
+
+#[tokio::test]
+async fn friend_and_stranger_info_use_v11_names() {
+    let (mock, _adapter, act) = setup(16);
+    mock.respond(
+        "get_friend_list",
+        MockResponse::Ok(json!([
+            { "user_id": "42", "user_name": "alice", "user_displayname": "", "user_remark": "A" },
+            { "user_id": "43", "user_name": "bob", "user_displayname": "", "user_remark": "" },
+        ])),
+    );
+    let friends = act.get_friend_list().await.unwrap();
+    assert_eq!(friends.len(), 2);
+    assert_eq!(friends[0].user_id, 42);
+    assert_eq!(friends[0].nickname, "alice");
+    assert_eq!(friends[0].remark, "A");
+
+    mock.respond(
+        "get_user_info",
+        MockResponse::Ok(json!({ "user_id": "44", "user_name": "carol", "user_displayname": "" })),
+    );
+    let data = NapcatRequestData::new()
+        .with_action("get_stranger_info")
+        .with_params(json!({ "user_id": 44 }));
+    let info = act.request(data).await.unwrap();
+    assert_eq!(info["data"]["user_id"], 44);
+    assert_eq!(info["data"]["nickname"], "carol");
+}
+
+#[tokio::test]
+async fn group_member_info_uses_v11_names() {
+    let (mock, _adapter, act) = setup(16);
+    let member = |id: &str, name: &str, card: &str| json!({ "user_id": id, "user_name": name, "user_displayname": card });
+    mock.respond(
+        "get_group_member_info",
+        MockResponse::Ok(member("42", "alice", "群名片")),
+    );
+    let info = act.get_group_member_info(20001, 42, false).await.unwrap();
+    assert_eq!(info.group_id, 20001);
+    assert_eq!(info.user_id, 42);
+    assert_eq!(info.nickname, "alice");
+    assert_eq!(info.card, "群名片");
+
+    for _ in 0..2 {
+        mock.respond(
+            "get_group_member_list",
+            MockResponse::Ok(json!([member("42", "alice", ""), member("43", "bob", "B")])),
+        );
+    }
+    let members = act.get_group_member_list(20001).await.unwrap();
+    assert_eq!(members.len(), 2);
+    assert!(members.iter().all(|member| member.group_id == 20001));
+    assert_eq!(members[1].nickname, "bob");
+    // 经由缓存同样可用
+    assert_eq!(
+        act.cached_display_name(20001, 43).await.as_deref(),
+        Some("B")
+    );
+    assert_eq!(
+        act.cached_display_name(20001, 42).await.as_deref(),
+        Some("alice")
+    );
+}
@@ -0,0 +1,170 @@
+use axum::{Json, Router, routing::post};
+use meril_cat::{
+    plugins::{ai_chat::AiChatPlugin, get_help::HelpPlugin},
+    prelude::PluginWrapper,
+    testing::{MockResponse, TEST_SELF_ID, TestHarness},
+};
+use serde_json::{Value, json};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{Mutex, RwLock};
+
+const WAIT: Duration = Duration::from_secs(1);
+
+fn help_plugin() -> HelpPlugin {
+    let plugins = vec![Arc::new(
+        PluginWrapper::new(AiChatPlugin::new(""))
+            .with_name("Ai Chat In QQ")
+            .with_description("Any Triggle"),
+    )];
+    HelpPlugin::new(Arc::new(RwLock::new(plugins)))
+}
+
+#[tokio::test]
+async fn help_lists_plugins() {
+    let harness = TestHarness::new();
+    harness.load_plugin(help_plugin()).await;
+    harness.adapter.push_private_message(42, "/help");
+
+    let sent = harness
+        .adapter
+        .wait_for_action("send_private_forward_msg", 0, WAIT)
+        .await
+        .expect("help reply");
+    assert_eq!(sent.params()["user_id"], 42);
+    assert_eq!(sent.self_id(), Some(TEST_SELF_ID));
+    let nodes = sent.params()["messages"].as_array().unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0]["type"], "node");
+    assert_eq!(nodes[0]["data"]["user_id"], TEST_SELF_ID);
+    assert_eq!(
+        nodes[0]["data"]["content"][0]["data"]["text"],
+        "[PluginList]"
+    );
+    let text = nodes[1]["data"]["content"][0]["data"]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("Ai Chat In QQ"));
+}
+
+#[tokio::test]
+async fn help_ignores_other_messages() {
+    let harness = TestHarness::new();
+    harness.load_plugin(help_plugin()).await;
+    harness.adapter.push_private_message(42, "hello");
+    harness.settle().await;
+    assert!(harness.adapter.sent_actions().await.is_empty());
+}
+
+#[tokio::test]
+async fn help_survives_failed_send() {
+    let harness = TestHarness::new();
+    harness.adapter.respond(
+        "send_private_forward_msg",
+        MockResponse::Failed {
+            retcode: 1200,
+            message: "failed".into(),
+        },
+    );
+    harness.load_plugin(help_plugin()).await;
+    harness.adapter.push_private_message(42, "/help");
+    harness
+        .adapter
+        .wait_for_action("send_private_forward_msg", 0, WAIT)
+        .await
+        .expect("first reply");
+    harness.settle().await;
+
+    harness.adapter.push_private_message(42, "/help");
+    harness
+        .adapter
+        .wait_for_action("send_private_forward_msg", 1, WAIT)
+        .await
+        .expect("second reply");
+}
+
+#[tokio::test]
+async fn ai_chat_reports_mood() {
+    let harness = TestHarness::new();
+    harness.load_plugin(AiChatPlugin::new("")).await;
+    harness.adapter.push_private_message(42, "/mood");
+
+    let sent = harness
+        .adapter
+        .wait_for_action("send_private_msg", 0, WAIT)
+        .await
+        .expect("mood reply");
+    assert_eq!(sent.params()["user_id"], 42);
+    let text = sent.params()["message"][0]["data"]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.starts_with("[Mood]"));
+}
+
+#[tokio::test]
+async fn ai_chat_without_token_stays_silent() {
+    let harness = TestHarness::new();
+    harness.load_plugin(AiChatPlugin::new("")).await;
+    harness.adapter.push_private_message(42, "hello");
+    harness.settle().await;
+    assert!(harness.adapter.sent_actions().await.is_empty());
+}
+
+/// 代替 DeepSeek 的聊天接口, 固定回复 reply, 并记下收到的请求
+async fn spawn_llm_stand_in(reply: &'static str) -> (String, Arc<Mutex<Vec<Value>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    let router = Router::new().route(
+        "/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let seen = seen.clone();
+            async move {
+                seen.lock().await.push(body);
+                Json(json!({
+                    "choices": [{
+                        "index": 0,
+                        "message": { "role": "assistant", "content": reply },
+                        "logprobs": null,
+                        "finish_reason": "stop",
+                    }],
+                    "usage": {
+                        "completion_tokens": 1,
+                        "prompt_tokens": 1,
+                        "prompt_cache_hit_tokens": 0,
+                        "prompt_cache_miss_tokens": 1,
+                        "total_tokens": 2,
+                    },
+                }))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}", addr), requests)
+}
+
+#[tokio::test]
+async fn ai_chat_replies_in_bubbles() {
+    let (base_url, requests) = spawn_llm_stand_in("哼;才没有想你呢").await;
+    let harness = TestHarness::new();
+    harness
+        .load_plugin(AiChatPlugin::new("token").with_base_url(base_url))
+        .await;
+    harness.adapter.push_private_message(42, "hello");
+
+    let mut bubbles = Vec::new();
+    for index in 0..2 {
+        let sent = harness
+            .adapter
+            .wait_for_action("send_private_msg", index, WAIT)
+            .await
+            .expect("chat reply");
+        assert_eq!(sent.params()["user_id"], 42);
+        assert_eq!(sent.self_id(), Some(TEST_SELF_ID));
+        bubbles.push(sent.params()["message"][0]["data"]["text"].clone());
+    }
+    assert_eq!(bubbles, ["哼", "才没有想你呢"]);
+    let requests = requests.lock().await;
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].to_string().contains("hello"));
+}
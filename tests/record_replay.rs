@@ -0,0 +1,88 @@
+use meril_cat::{
+    core::{
+        action::ActionManager,
+        event::EventManager,
+        record::{RecordKind, RecordingAdapter, ReplayAdapter, read_recording},
+    },
+    plugins::get_help::HelpPlugin,
+    prelude::{Adapter, PluginWrapper},
+    testing::{MockAdapter, MockResponse},
+};
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+
+const WAIT: Duration = Duration::from_secs(1);
+
+fn start(adapter: Arc<dyn Adapter>) -> Arc<ActionManager> {
+    let event = EventManager::new(adapter.get_event_port(), adapter.get_state_port());
+    let action = ActionManager::new(adapter.get_action_port(), adapter.get_state_port());
+    let plugin = Arc::new(PluginWrapper::new(HelpPlugin::new(Arc::new(RwLock::new(
+        vec![],
+    )))));
+    tokio::spawn(plugin.run(event.get_event_nexus(), action.clone()));
+    adapter.run();
+    event.run();
+    action.clone().run();
+    action
+}
+
+#[tokio::test]
+async fn recorded_session_replays_deterministically() {
+    let path = std::env::temp_dir().join(format!("meril-record-{}.jsonl", std::process::id()));
+
+    let mock = MockAdapter::new();
+    mock.respond(
+        "send_private_forward_msg",
+        MockResponse::Ok(json!({ "message_id": 5 })),
+    );
+    let recording = RecordingAdapter::new(mock.clone(), &path).unwrap();
+    start(recording);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    mock.connect(10000);
+    mock.push_private_message(42, "/help");
+    mock.wait_for_action("send_private_forward_msg", 0, WAIT)
+        .await
+        .expect("reply while recording");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let entries = read_recording(&path).unwrap();
+    let kinds: Vec<RecordKind> = entries.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            RecordKind::State,
+            RecordKind::Event,
+            RecordKind::Action,
+            RecordKind::Response
+        ]
+    );
+
+    let replay = Arc::new(
+        ReplayAdapter::new(entries)
+            .with_start_delay(Duration::from_millis(50))
+            .with_gap(Duration::from_millis(50), Duration::from_millis(100)),
+    );
+    let action = start(replay.clone());
+    tokio::time::timeout(WAIT, replay.finished()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let recorded = replay.recorded_actions();
+    let replayed = replay.replayed_actions();
+    assert_eq!(replayed.len(), 1);
+    assert_eq!(replayed[0]["action"], recorded[0]["action"]);
+    assert_eq!(replayed[0]["params"], recorded[0]["params"]);
+
+    // 录制中没有的动作以空数据回复, 而不是超时
+    let res = action
+        .request(
+            meril_cat::prelude::NapcatRequestData::new()
+                .with_action("get_status")
+                .with_params(json!({})),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res["status"], "ok");
+
+    let _ = std::fs::remove_file(&path);
+}
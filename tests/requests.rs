@@ -0,0 +1,212 @@
+use meril_cat::{
+    plugins::request::RequestPlugin,
+    testing::{TEST_SELF_ID, TestHarness},
+    types::event_type::request_event::{GroupRequestEvent, RequestEvent},
+};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(1);
+
+fn friend_request(user_id: i64, flag: &str) -> Value {
+    json!({
+        "post_type": "request",
+        "request_type": "friend",
+        "self_id": TEST_SELF_ID,
+        "time": 0,
+        "user_id": user_id,
+        "comment": "hi",
+        "flag": flag,
+    })
+}
+
+fn group_invite(user_id: i64, flag: &str) -> Value {
+    json!({
+        "post_type": "request",
+        "request_type": "group",
+        "sub_type": "invite",
+        "self_id": TEST_SELF_ID,
+        "time": 0,
+        "group_id": 100,
+        "user_id": user_id,
+        "comment": "",
+        "flag": flag,
+    })
+}
+
+#[tokio::test]
+async fn requests_reach_typed_ports() {
+    let harness = TestHarness::new();
+    let request_port = harness.event_nexus.get_request_port();
+    let friend_port = harness.event_nexus.get_friend_request_port();
+    let group_port = harness.event_nexus.get_group_request_port();
+    harness.adapter.push_event(friend_request(7, "f1"));
+    harness.adapter.push_event(group_invite(7, "g1"));
+
+    let friend = tokio::time::timeout(WAIT, friend_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(friend.flag, "f1");
+    assert_eq!(friend.comment, "hi");
+    let group = tokio::time::timeout(WAIT, group_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(group.is_invite());
+    let first = tokio::time::timeout(WAIT, request_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(*first, RequestEvent::Friend(_)));
+}
+
+#[tokio::test]
+async fn approve_and_reject_call_actions() {
+    let harness = TestHarness::new();
+    let request: GroupRequestEvent = serde_json::from_value(group_invite(7, "g1")).unwrap();
+    request.approve(&harness.action).await.unwrap();
+    request.reject(&harness.action, "no").await.unwrap();
+
+    let sent = harness.adapter.sent_actions().await;
+    assert_eq!(sent[0].action(), "set_group_add_request");
+    assert_eq!(sent[0].self_id(), Some(TEST_SELF_ID));
+    assert_eq!(
+        *sent[0].params(),
+        json!({ "flag": "g1", "sub_type": "invite", "approve": true, "reason": "" })
+    );
+    assert_eq!(sent[1].params()["approve"], false);
+    assert_eq!(sent[1].params()["reason"], "no");
+}
+
+#[tokio::test]
+async fn invites_from_admin_are_accepted() {
+    let harness = TestHarness::new();
+    harness.load_plugin(RequestPlugin::new(42)).await;
+    harness.adapter.push_event(group_invite(42, "g1"));
+
+    let sent = harness
+        .adapter
+        .wait_for_action("set_group_add_request", 0, WAIT)
+        .await
+        .expect("auto approve");
+    assert_eq!(sent.params()["approve"], true);
+}
+
+#[tokio::test]
+async fn other_requests_wait_for_admin() {
+    let harness = TestHarness::new();
+    harness.load_plugin(RequestPlugin::new(42)).await;
+    harness.adapter.push_event(friend_request(7, "f1"));
+
+    let notify = harness
+        .adapter
+        .wait_for_action("send_private_msg", 0, WAIT)
+        .await
+        .expect("ask admin");
+    assert_eq!(notify.params()["user_id"], 42);
+    let text = notify.params()["message"][0]["data"]["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(text.contains("/approve f1"));
+
+    // 非管理员无法处理
+    harness.adapter.push_private_message(7, "/approve f1");
+    harness.settle().await;
+    harness.adapter.push_private_message(42, "/reject f1 spam");
+    let sent = harness
+        .adapter
+        .wait_for_action("set_friend_add_request", 0, WAIT)
+        .await
+        .expect("reject");
+    assert_eq!(sent.params()["flag"], "f1");
+    assert_eq!(sent.params()["approve"], false);
+    let reply = harness
+        .adapter
+        .wait_for_action("send_private_msg", 1, WAIT)
+        .await
+        .expect("reply");
+    assert!(
+        reply.params()["message"][0]["data"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("已拒绝")
+    );
+}
+
+/// 管理员发送指令后收到的回复
+async fn admin_reply(harness: &TestHarness, command: &str, index: usize) -> String {
+    harness.adapter.push_private_message(42, command);
+    let reply = harness
+        .adapter
+        .wait_for_action("send_private_msg", index, WAIT)
+        .await
+        .expect("reply");
+    reply.params()["message"][0]["data"]["text"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn pending_requests_beyond_capacity_drop_oldest() {
+    let harness = TestHarness::new();
+    let plugin = RequestPlugin::new(42).with_pending_limit(1, Duration::from_secs(60));
+    harness.load_plugin(plugin).await;
+    for (index, flag) in ["f1", "f2"].into_iter().enumerate() {
+        harness.adapter.push_event(friend_request(7, flag));
+        harness
+            .adapter
+            .wait_for_action("send_private_msg", index, WAIT)
+            .await
+            .expect("ask admin");
+    }
+
+    assert!(
+        admin_reply(&harness, "/approve f1", 2)
+            .await
+            .contains("没有待处理的请求")
+    );
+    assert!(
+        admin_reply(&harness, "/approve f2", 3)
+            .await
+            .contains("已同意")
+    );
+    let sent = harness.adapter.sent_actions().await;
+    let handled: Vec<_> = sent
+        .iter()
+        .filter(|data| data.action() == "set_friend_add_request")
+        .map(|data| data.params()["flag"].clone())
+        .collect();
+    assert_eq!(handled, ["f2"]);
+}
+
+#[tokio::test]
+async fn expired_requests_cannot_be_handled() {
+    let harness = TestHarness::new();
+    let plugin = RequestPlugin::new(42).with_pending_limit(10, Duration::from_millis(50));
+    harness.load_plugin(plugin).await;
+    harness.adapter.push_event(friend_request(7, "f1"));
+    harness
+        .adapter
+        .wait_for_action("send_private_msg", 0, WAIT)
+        .await
+        .expect("ask admin");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(
+        admin_reply(&harness, "/approve f1", 1)
+            .await
+            .contains("没有待处理的请求")
+    );
+    harness.settle().await;
+    assert!(
+        harness
+            .adapter
+            .sent_actions()
+            .await
+            .iter()
+            .all(|data| data.action() != "set_friend_add_request")
+    );
+}
@@ -0,0 +1,83 @@
+use meril_cat::{
+    plugins::get_help::HelpPlugin,
+    testing::{TEST_SELF_ID, TestHarness, group_message, private_message},
+    types::event_type::message_event::MessageEvent,
+};
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+
+const WAIT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn message_sent_has_its_own_port() {
+    let harness = TestHarness::new();
+    let sent_port = harness.event_nexus.get_message_sent_port();
+    let group_port = harness.event_nexus.get_group_message_port();
+    let all_port = harness.event_nexus.get_all_event_port();
+    let mut event = group_message(100, TEST_SELF_ID, "from bot");
+    event["post_type"] = json!("message_sent");
+    harness.adapter.push_event(event);
+
+    let sent = tokio::time::timeout(WAIT, sent_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(&*sent, MessageEvent::Group(e) if e.raw_message == "from bot"));
+    assert!(
+        tokio::time::timeout(QUIET, group_port.recv())
+            .await
+            .is_err()
+    );
+    assert!(tokio::time::timeout(QUIET, all_port.recv()).await.is_err());
+}
+
+#[tokio::test]
+async fn own_messages_are_not_plugin_input() {
+    let harness = TestHarness::new();
+    let sent_port = harness.event_nexus.get_message_sent_port();
+    let private_port = harness.event_nexus.get_private_message_port();
+    let all_port = harness.event_nexus.get_all_event_port();
+    let mut event = private_message(TEST_SELF_ID, "echo");
+    event["target_id"] = json!(42);
+    harness.adapter.push_event(event);
+
+    let sent = tokio::time::timeout(WAIT, sent_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(sent.is_from_self());
+    assert!(matches!(&*sent, MessageEvent::Private(e) if e.target_id == Some(42)));
+    assert!(
+        tokio::time::timeout(QUIET, private_port.recv())
+            .await
+            .is_err()
+    );
+    assert!(tokio::time::timeout(QUIET, all_port.recv()).await.is_err());
+
+    // 他人的消息照常送达
+    harness.adapter.push_private_message(42, "hello");
+    let msg = tokio::time::timeout(WAIT, private_port.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(msg.sender.user_id, 42);
+    assert!(
+        tokio::time::timeout(WAIT, all_port.recv())
+            .await
+            .unwrap()
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn plugins_ignore_own_commands() {
+    let harness = TestHarness::new();
+    harness
+        .load_plugin(HelpPlugin::new(Arc::new(RwLock::new(Vec::new()))))
+        .await;
+    harness.adapter.push_private_message(TEST_SELF_ID, "/help");
+    harness.settle().await;
+    assert!(harness.adapter.sent_actions().await.is_empty());
+}
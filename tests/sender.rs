@@ -0,0 +1,88 @@
+use meril_cat::{
+    testing::{TEST_SELF_ID, TestHarness, group_message, private_message},
+    types::event_type::message_event::{GroupMessageEvent, PrivateMessageEvent},
+};
+use serde_json::json;
+
+#[test]
+fn group_sender_details_are_parsed() {
+    let mut event = group_message(100, 42, "hi");
+    event["sender"] = json!({
+        "user_id": 42,
+        "nickname": "nick",
+        "card": "",
+        "role": "admin",
+        "title": "king",
+        "level": "12",
+        "age": 18,
+        "sex": "female",
+    });
+    event["font"] = json!(14);
+    event["message_format"] = json!("array");
+    event["real_id"] = json!(777);
+    let msg: GroupMessageEvent = serde_json::from_value(event).unwrap();
+
+    assert_eq!(msg.sub_type, "normal");
+    assert_eq!(msg.sender.level, "12");
+    assert_eq!(msg.sender.title, "king");
+    assert_eq!(msg.sender.age, 18);
+    assert_eq!(msg.sender.sex, "female");
+    assert_eq!(msg.real_id, 777);
+    assert_eq!(msg.font, 14);
+    assert!(msg.sender.is_admin());
+    assert!(!msg.sender.is_owner());
+    assert_eq!(msg.display_name(), "nick");
+    assert!(!msg.is_anonymous());
+}
+
+#[test]
+fn owner_counts_as_admin_and_card_wins() {
+    let mut event = group_message(100, 42, "hi");
+    event["sender"] = json!({
+        "user_id": 42, "nickname": "nick", "card": "Card", "role": "owner", "level": 3,
+    });
+    let msg: GroupMessageEvent = serde_json::from_value(event).unwrap();
+    assert!(msg.sender.is_owner());
+    assert!(msg.sender.is_admin());
+    assert_eq!(msg.sender.level, "3");
+    assert_eq!(msg.display_name(), "Card");
+}
+
+#[test]
+fn anonymous_messages_use_anonymous_name() {
+    let mut event = group_message(100, 80000000, "who am i");
+    event["sub_type"] = json!("anonymous");
+    event["anonymous"] = json!({ "id": 1, "name": "匿名者", "flag": "abc" });
+    let msg: GroupMessageEvent = serde_json::from_value(event).unwrap();
+    assert!(msg.is_anonymous());
+    assert_eq!(msg.display_name(), "匿名者");
+    assert_eq!(msg.anonymous.unwrap().flag, "abc");
+}
+
+#[test]
+fn temp_session_private_messages() {
+    let mut event = private_message(42, "hello");
+    event["sub_type"] = json!("group");
+    event["temp_source"] = json!(0);
+    event["sender"]["group_id"] = json!(100);
+    let msg: PrivateMessageEvent = serde_json::from_value(event).unwrap();
+    assert!(msg.is_temp());
+    assert_eq!(msg.temp_source, Some(0));
+    assert_eq!(msg.sender.group_id, Some(100));
+    assert!(!msg.sender.is_admin());
+}
+
+#[tokio::test]
+async fn anonymous_ban_uses_flag() {
+    let harness = TestHarness::new();
+    harness
+        .action
+        .for_account(TEST_SELF_ID)
+        .set_group_anonymous_ban(100, "abc", 600)
+        .await
+        .unwrap();
+    let sent = harness.adapter.sent_actions().await;
+    assert_eq!(sent[0].action(), "set_group_anonymous_ban");
+    assert_eq!(sent[0].params()["anonymous_flag"], "abc");
+    assert_eq!(sent[0].params()["duration"], 600);
+}
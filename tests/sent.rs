@@ -0,0 +1,218 @@
+use meril_cat::{
+    core::{
+        limiter::Conversation,
+        sent::{SentMessage, SentStore},
+    },
+    plugins::recall::RecallPlugin,
+    prelude::Message,
+    testing::{TEST_SELF_ID, TestHarness},
+};
+use std::time::Duration;
+
+const WAIT: Duration = Duration::from_secs(1);
+
+fn text(s: &str) -> Message {
+    Message::new().with_text(s)
+}
+
+#[tokio::test]
+async fn send_returns_handle_and_records_it() {
+    let harness = TestHarness::new();
+    let act = harness.action.for_account(TEST_SELF_ID);
+    let first = act.send_group_message(100, text("a")).await.unwrap();
+    let second = act.send_private_message(42, text("b")).await.unwrap();
+
+    assert_eq!(first.target, Conversation::Group(100));
+    assert_eq!(first.self_id, Some(TEST_SELF_ID));
+    assert_ne!(first.message_id, second.message_id);
+    assert_eq!(
+        act.recent_sent(None, 10),
+        vec![second.clone(), first.clone()]
+    );
+    assert_eq!(
+        act.recent_sent(Some(Conversation::Group(100)), 10),
+        vec![first]
+    );
+    // 其他账号看不到本账号发出的消息
+    assert!(
+        harness
+            .action
+            .for_account(1)
+            .recent_sent(None, 10)
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn recall_deletes_and_forgets() {
+    let harness = TestHarness::new();
+    let act = harness.action.clone();
+    let sent = act.send_private_message(42, text("oops")).await.unwrap();
+    sent.recall(&act).await.unwrap();
+
+    let delete = harness
+        .adapter
+        .wait_for_action("delete_msg", 0, WAIT)
+        .await
+        .expect("delete_msg");
+    assert_eq!(delete.params()["message_id"], sent.message_id);
+    assert!(act.recent_sent(None, 10).is_empty());
+}
+
+#[tokio::test]
+async fn reply_quotes_original() {
+    let harness = TestHarness::new();
+    let act = harness.action.clone();
+    let sent = act.send_group_message(100, text("hi")).await.unwrap();
+    let reply = sent.reply(&act, text("again")).await.unwrap();
+    assert_eq!(reply.target, Conversation::Group(100));
+
+    let data = harness
+        .adapter
+        .wait_for_action("send_group_msg", 1, WAIT)
+        .await
+        .expect("reply");
+    let message = data.params()["message"].as_array().unwrap().clone();
+    assert_eq!(message[0]["type"], "reply");
+    assert_eq!(message[0]["data"]["id"], sent.message_id.to_string());
+    assert_eq!(message[1]["data"]["text"], "again");
+}
+
+#[tokio::test]
+async fn edit_recalls_then_resends() {
+    let harness = TestHarness::new();
+    let act = harness.action.clone();
+    let sent = act.send_private_message(42, text("typo")).await.unwrap();
+    let edited = sent
+        .edit_by_recall_and_resend(&act, text("fixed"))
+        .await
+        .unwrap();
+
+    let actions: Vec<String> = harness
+        .adapter
+        .sent_actions()
+        .await
+        .iter()
+        .map(|data| data.action().to_string())
+        .collect();
+    assert_eq!(
+        actions,
+        ["send_private_msg", "delete_msg", "send_private_msg"]
+    );
+    assert_eq!(act.recent_sent(None, 10), vec![edited]);
+}
+
+#[tokio::test]
+async fn follow_ups_use_the_sending_account() {
+    let harness = TestHarness::new();
+    let sent = harness
+        .action
+        .for_account(10001)
+        .send_group_message(100, text("hi"))
+        .await
+        .unwrap();
+    // 即使经未指定账号的实例操作, 也由原账号撤回与回复
+    let unscoped = harness.action.clone();
+    let reply = sent.reply(&unscoped, text("again")).await.unwrap();
+    assert_eq!(reply.self_id, Some(10001));
+    sent.recall(&unscoped).await.unwrap();
+
+    for data in harness.adapter.sent_actions().await {
+        assert_eq!(data.self_id(), Some(10001), "{}", data.action());
+    }
+}
+
+#[tokio::test]
+async fn dry_run_messages_are_not_recorded() {
+    let harness = TestHarness::new();
+    let act = harness.action.for_account(TEST_SELF_ID).dry_run();
+    let sent = act.send_group_message(100, text("hi")).await.unwrap();
+    assert!(sent.message_id < 0);
+    assert!(act.recent_sent(None, 10).is_empty());
+    assert!(harness.action.sent_store().is_empty());
+}
+
+#[test]
+fn store_drops_oldest_beyond_capacity() {
+    let store = SentStore::new(2);
+    for message_id in 1..=3 {
+        store.push(SentMessage {
+            message_id,
+            self_id: None,
+            target: Conversation::Private(1),
+            time: 0,
+        });
+    }
+    let ids: Vec<i64> = store
+        .recent(None, None, 10)
+        .iter()
+        .map(|sent| sent.message_id)
+        .collect();
+    assert_eq!(ids, [3, 2]);
+}
+
+#[tokio::test]
+async fn recall_command_recalls_last_n_in_conversation() {
+    let harness = TestHarness::new();
+    harness.load_plugin(RecallPlugin::new(42)).await;
+    let act = harness.action.for_account(TEST_SELF_ID);
+    let mut ids = Vec::new();
+    for i in 0..4 {
+        let sent = act.send_group_message(100, text(&i.to_string())).await;
+        ids.push(sent.unwrap().message_id);
+    }
+    act.send_group_message(200, text("elsewhere"))
+        .await
+        .unwrap();
+
+    harness
+        .adapter
+        .push_group_message(100, 42, "/recall last 3");
+    let mut recalled = Vec::new();
+    for index in 0..3 {
+        let data = harness
+            .adapter
+            .wait_for_action("delete_msg", index, WAIT)
+            .await
+            .expect("delete_msg");
+        recalled.push(data.params()["message_id"].as_i64().unwrap());
+    }
+    assert_eq!(recalled, [ids[3], ids[2], ids[1]]);
+    harness.settle().await;
+    assert_eq!(act.recent_sent(Some(Conversation::Group(100)), 10).len(), 1);
+    assert_eq!(act.recent_sent(Some(Conversation::Group(200)), 10).len(), 1);
+}
+
+#[tokio::test]
+async fn recall_command_ignores_non_admin() {
+    let harness = TestHarness::new();
+    harness.load_plugin(RecallPlugin::new(42)).await;
+    let act = harness.action.for_account(TEST_SELF_ID);
+    act.send_private_message(7, text("hi")).await.unwrap();
+
+    harness.adapter.push_private_message(7, "/recall");
+    harness.settle().await;
+    assert_eq!(act.recent_sent(None, 10).len(), 1);
+}
+
+#[tokio::test]
+async fn recall_command_finds_messages_sent_without_account() {
+    let harness = TestHarness::new();
+    harness.load_plugin(RecallPlugin::new(42)).await;
+    let sent = harness
+        .action
+        .send_group_message(100, text("hi"))
+        .await
+        .unwrap();
+    assert_eq!(sent.self_id, None);
+
+    harness.adapter.push_group_message(100, 42, "/recall");
+    let data = harness
+        .adapter
+        .wait_for_action("delete_msg", 0, WAIT)
+        .await
+        .expect("delete_msg");
+    assert_eq!(data.params()["message_id"], sent.message_id);
+    // 由收到指令的账号撤回
+    assert_eq!(data.self_id(), Some(TEST_SELF_ID));
+}
@@ -0,0 +1,113 @@
+use meril_cat::{
+    testing::{MockResponse, TestHarness},
+    types::action_type::{ActionError, RetryPolicy},
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn per_call_timeout_overrides_default() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("get_friend_list", MockResponse::NoReply);
+    let start = Instant::now();
+    let err = harness
+        .action
+        .with_timeout(Duration::from_millis(100))
+        .get_friend_list()
+        .await
+        .unwrap_err();
+    assert_eq!(err, ActionError::Timeout);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(harness.action.pending_count(), 0);
+}
+
+#[tokio::test]
+async fn per_action_timeout_is_shared() {
+    let harness = TestHarness::new();
+    harness
+        .action
+        .for_account(1)
+        .set_action_timeout("get_group_list", Duration::from_millis(80));
+    assert_eq!(
+        harness.action.timeout_for("get_group_list"),
+        Duration::from_millis(80)
+    );
+    assert_eq!(
+        harness
+            .action
+            .with_timeout(Duration::from_secs(60))
+            .timeout_for("get_group_list"),
+        Duration::from_secs(60)
+    );
+    harness
+        .adapter
+        .respond("get_group_list", MockResponse::NoReply);
+    assert_eq!(
+        harness.action.get_group_list().await.unwrap_err(),
+        ActionError::Timeout
+    );
+}
+
+#[tokio::test]
+async fn dropped_request_is_cleaned_up() {
+    let harness = TestHarness::new();
+    harness.adapter.respond("get_status", MockResponse::NoReply);
+    let act = harness.action.clone();
+    let task = tokio::spawn(async move { act.get_status().await });
+    harness
+        .adapter
+        .wait_for_action("get_status", 0, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(harness.action.pending_count(), 1);
+    task.abort();
+    let _ = task.await;
+    assert_eq!(harness.action.pending_count(), 0);
+}
+
+#[tokio::test]
+async fn idempotent_actions_are_retried() {
+    let harness = TestHarness::new();
+    harness
+        .adapter
+        .respond("get_login_info", MockResponse::NoReply);
+    harness.adapter.respond(
+        "get_login_info",
+        MockResponse::Ok(json!({ "user_id": 1, "nickname": "bot" })),
+    );
+    let info = harness
+        .action
+        .with_timeout(Duration::from_millis(50))
+        .with_retry(RetryPolicy::new(2, Duration::from_millis(10)))
+        .get_login_info()
+        .await
+        .unwrap();
+    assert_eq!(info.nickname, "bot");
+    assert_eq!(harness.adapter.sent_actions().await.len(), 2);
+}
+
+#[tokio::test]
+async fn failures_and_writes_are_not_retried() {
+    let harness = TestHarness::new();
+    let act = harness
+        .action
+        .with_timeout(Duration::from_millis(50))
+        .with_retry(RetryPolicy::new(3, Duration::from_millis(10)));
+
+    harness.adapter.respond(
+        "get_msg",
+        MockResponse::Failed {
+            retcode: 1200,
+            message: "not found".into(),
+        },
+    );
+    assert!(act.get_msg(1).await.is_err());
+
+    harness.adapter.respond("delete_msg", MockResponse::NoReply);
+    assert_eq!(act.delete_msg(1).await.unwrap_err(), ActionError::Timeout);
+
+    let sent = harness.adapter.sent_actions().await;
+    assert_eq!(sent.len(), 2);
+}
@@ -0,0 +1,143 @@
+use hmac::{Hmac, Mac};
+use meril_cat::{
+    core::adapter::{NapcatAdapter, NapcatOptions, QUICK_OPERATION_ACTION},
+    prelude::Adapter,
+};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use sha1::Sha1;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+const WAIT: Duration = Duration::from_secs(1);
+
+/// 在随机端口上提供 HTTP 上报服务, 返回 /webhook 地址
+async fn serve(options: NapcatOptions) -> (Arc<NapcatAdapter>, String) {
+    let adapter = NapcatAdapter::with_options(options);
+    adapter.clone().run_detached();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let service = adapter
+        .clone()
+        .webhook_router()
+        .into_make_service_with_connect_info::<SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, service).await.unwrap() });
+    (adapter, format!("http://{}/webhook", addr))
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha1={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn message_event() -> Value {
+    json!({
+        "post_type": "message",
+        "message_type": "private",
+        "self_id": 10001,
+        "user_id": 42,
+        "message_id": 7,
+    })
+}
+
+async fn post(url: &str, body: &Value, signature: Option<String>) -> reqwest::Response {
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .body(serde_json::to_vec(body).unwrap());
+    if let Some(signature) = signature {
+        request = request.header("X-Signature", signature);
+    }
+    request.send().await.unwrap()
+}
+
+fn signed_options() -> NapcatOptions {
+    NapcatOptions {
+        http_secret: "secret".into(),
+        quick_timeout: Duration::from_millis(100),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn valid_signature_is_accepted() {
+    let (adapter, url) = serve(signed_options()).await;
+    let events = adapter.get_event_port();
+    let event = json!({ "post_type": "meta_event", "self_id": 10001 });
+    let body = serde_json::to_vec(&event).unwrap();
+    let res = post(&url, &event, Some(sign("secret", &body))).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    let received = tokio::time::timeout(WAIT, events.recv()).await.unwrap();
+    assert_eq!(received.unwrap(), event);
+}
+
+#[tokio::test]
+async fn invalid_or_missing_signature_is_rejected() {
+    let (adapter, url) = serve(signed_options()).await;
+    let events = adapter.get_event_port();
+    let event = message_event();
+    let body = serde_json::to_vec(&event).unwrap();
+
+    let res = post(&url, &event, Some(sign("wrong", &body))).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = post(&url, &event, Some("sha1=not-hex".into())).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = post(&url, &event, None).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    // 被拒绝的上报不会进入事件流
+    let received = tokio::time::timeout(Duration::from_millis(100), events.recv()).await;
+    assert!(received.is_err());
+}
+
+#[tokio::test]
+async fn quick_operation_is_returned_as_response_body() {
+    let (adapter, url) = serve(NapcatOptions::default()).await;
+    let events = adapter.get_event_port();
+    let actions = adapter.get_action_port();
+    let event = message_event();
+    let request = tokio::spawn({
+        let url = url.clone();
+        let event = event.clone();
+        async move { post(&url, &event, None).await }
+    });
+
+    let context = tokio::time::timeout(WAIT, events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let operation = json!({ "reply": [{ "type": "text", "data": { "text": "hi" } }] });
+    actions
+        .send(json!({
+            "action": QUICK_OPERATION_ACTION,
+            "params": { "context": context, "operation": operation },
+            "echo": "quick",
+        }))
+        .unwrap();
+
+    let res = request.await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.json::<Value>().await.unwrap(), operation);
+    // 快速操作由上报响应承接, 动作本身直接得到成功回执
+    let reply = tokio::time::timeout(WAIT, async {
+        loop {
+            let value = actions.recv().await.unwrap();
+            if value["echo"] == "quick" && value.get("status").is_some() {
+                return value;
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(reply["retcode"], 0);
+}
+
+#[tokio::test]
+async fn webhook_answers_no_content_when_quick_operation_times_out() {
+    let (_adapter, url) = serve(NapcatOptions {
+        quick_timeout: Duration::from_millis(50),
+        ..Default::default()
+    })
+    .await;
+    let res = post(&url, &message_event(), None).await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert!(res.bytes().await.unwrap().is_empty());
+}